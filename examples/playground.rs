@@ -1,6 +1,6 @@
 use sigma::Sigma;
 fn main() -> Result<(), ()> {
-  let result = Sigma::new("my username is {{ username: u32 | UPPERCASE }} WOW!")
+  let result = Sigma::new("my username is {{ username: str | UPPERCASE }} WOW!")
       .bind("username", "shekohex")
       .parse()
       .map_err(|e| eprintln!("Parse Error:\n{}", e))? // error handling..
@@ -0,0 +1,213 @@
+//! `sigma-lsp`, a language server for the sigma templates over stdio.
+//!
+//! it reports the errors of a template when it's opened and saved, completes
+//! the variables, the helpers, the data types and the macros, shows the
+//! declaration of a variable on hover and goes to the definition of a macro.
+mod server;
+
+use crate::server::Server;
+use serde_json::Value;
+use std::{
+  io::{self, BufRead, Write},
+  process::ExitCode,
+};
+
+fn main() -> ExitCode {
+  match serve(io::stdin().lock(), io::stdout().lock()) {
+    Ok(true) => ExitCode::SUCCESS,
+    // an `exit` without a `shutdown` first.
+    Ok(false) => ExitCode::FAILURE,
+    Err(e) => {
+      eprintln!("sigma-lsp: {}", e);
+      ExitCode::from(2)
+    },
+  }
+}
+
+/// answer the messages until `exit`, returns whether the client asked for a
+/// `shutdown` before.
+fn serve(mut reader: impl BufRead, mut writer: impl Write) -> io::Result<bool> {
+  let mut server = Server::default();
+  while let Some(body) = receive(&mut reader)? {
+    let message: Value = match serde_json::from_slice(&body) {
+      Ok(message) => message,
+      Err(e) => {
+        let reply =
+          server::error(Value::Null, server::PARSE_ERROR, &e.to_string());
+        send(&mut writer, &reply)?;
+        continue;
+      },
+    };
+    if message["method"] == "exit" {
+      return Ok(server.is_shut_down());
+    }
+    for reply in server.handle(message) {
+      send(&mut writer, &reply)?;
+    }
+  }
+  Ok(false)
+}
+
+/// the body of the next message, `None` once the input is closed.
+fn receive(reader: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+  let mut length = None;
+  loop {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+      return Ok(None);
+    }
+    let line = line.trim_end();
+    if line.is_empty() {
+      break;
+    }
+    if let Some((name, value)) = line.split_once(':') {
+      if name.eq_ignore_ascii_case("Content-Length") {
+        length = value.trim().parse().ok();
+      }
+    }
+  }
+  let length = length.ok_or_else(|| {
+    io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+  })?;
+  let mut body = vec![0; length];
+  reader.read_exact(&mut body)?;
+  Ok(Some(body))
+}
+
+fn send(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+  let body = message.to_string();
+  write!(writer, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+  writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use serde_json::json;
+
+  /// frame the messages, serve them and parse the replies.
+  fn run(messages: &[Value]) -> (bool, Vec<Value>) {
+    let mut input = Vec::new();
+    for message in messages {
+      send(&mut input, message).unwrap();
+    }
+    let mut output = Vec::new();
+    let shut_down = serve(io::Cursor::new(input), &mut output).unwrap();
+    let mut reader = io::Cursor::new(output);
+    let mut replies = Vec::new();
+    while let Some(body) = receive(&mut reader).unwrap() {
+      replies.push(serde_json::from_slice(&body).unwrap());
+    }
+    (shut_down, replies)
+  }
+
+  fn request(id: u32, method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+  }
+
+  fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+  }
+
+  fn at(line: u32, character: u32) -> Value {
+    json!({
+      "textDocument": { "uri": "file:///hi.sigma" },
+      "position": { "line": line, "character": character },
+    })
+  }
+
+  #[test]
+  fn session() {
+    let template = "{{# macro greet(name) }}Hi {{ name }}{{/ macro }}\n\
+                    {{ user?: str(len 1..9) | TRIM }} {{ age: u8 }}\n\
+                    {{> greet(user) }} {{ age: u }} {{ user | }}";
+    let fixed = template.replace("u }}", "u8 }}").replace("| }}", "}}");
+    let document = |text: &str| {
+      json!({
+        "uri": "file:///hi.sigma",
+        "languageId": "sigma",
+        "text": text,
+      })
+    };
+    let (shut_down, replies) = run(&[
+      request(1, "initialize", json!({ "capabilities": {} })),
+      notification("initialized", json!({})),
+      notification(
+        "textDocument/didOpen",
+        json!({ "textDocument": document(&fixed) }),
+      ),
+      notification(
+        "textDocument/didChange",
+        json!({
+          "textDocument": { "uri": "file:///hi.sigma", "version": 2 },
+          "contentChanges": [{ "text": template }],
+        }),
+      ),
+      notification(
+        "textDocument/didSave",
+        json!({
+          "textDocument": { "uri": "file:///hi.sigma" },
+        }),
+      ),
+      request(2, "textDocument/completion", at(2, 36)),
+      request(3, "textDocument/completion", at(2, 27)),
+      request(4, "textDocument/completion", at(2, 41)),
+      request(5, "textDocument/hover", at(1, 4)),
+      request(6, "textDocument/definition", at(2, 6)),
+      request(7, "textDocument/formatting", json!({})),
+      request(8, "shutdown", json!(null)),
+      notification("exit", json!(null)),
+    ]);
+    assert!(shut_down);
+    let capabilities = &replies[0]["result"]["capabilities"];
+    assert_eq!(capabilities["hoverProvider"], true);
+    // no diagnostics on open for the fixed version, then the saved errors.
+    assert_eq!(replies[1]["params"]["diagnostics"], json!([]));
+    let diagnostics = replies[2]["params"]["diagnostics"].as_array().unwrap();
+    assert_eq!(diagnostics.len(), 1, "{:?}", diagnostics);
+    let message = diagnostics[0]["message"].as_str().unwrap();
+    assert!(message.contains("did you mean: `u8`"), "{}", message);
+    assert_eq!(
+      diagnostics[0]["range"]["start"],
+      json!({ "line": 2, "character": 27 })
+    );
+    let labels = |reply: &Value| -> Vec<String> {
+      let items = reply["result"].as_array().unwrap().iter();
+      items
+        .map(|item| item["label"].as_str().unwrap().to_owned())
+        .collect()
+    };
+    // the variables of the last version that parsed.
+    assert_eq!(labels(&replies[3]), vec!["user", "age"]);
+    assert!(labels(&replies[4]).contains(&"datetime".to_owned()));
+    assert!(labels(&replies[5]).contains(&"UPPERCASE".to_owned()));
+    let hover = &replies[6]["result"]["contents"]["value"];
+    assert_eq!(hover, "```sigma\nuser?: str(len 1..9)\n```");
+    let definition = &replies[7]["result"]["range"];
+    assert_eq!(definition["start"], json!({ "line": 0, "character": 10 }));
+    assert_eq!(definition["end"], json!({ "line": 0, "character": 15 }));
+    assert_eq!(replies[8]["error"]["code"], -32601);
+    assert_eq!(replies[9]["result"], Value::Null);
+  }
+
+  #[test]
+  fn translated_messages() {
+    let document = json!({
+      "uri": "file:///hi.sigma",
+      "languageId": "sigma",
+      "text": "{{ t(\"hello\", name) }}",
+    });
+    let (_, replies) = run(&[notification(
+      "textDocument/didOpen",
+      json!({ "textDocument": document }),
+    )]);
+    assert_eq!(replies[0]["params"]["diagnostics"], json!([]));
+  }
+
+  #[test]
+  fn exit_without_shutdown() {
+    let (shut_down, replies) = run(&[notification("exit", json!(null))]);
+    assert!(!shut_down);
+    assert!(replies.is_empty());
+  }
+}
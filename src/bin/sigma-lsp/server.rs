@@ -0,0 +1,402 @@
+//! The open templates and the answers to the client.
+use pest::error::InputLocation;
+use serde_json::{json, Value};
+use sigma::{MemoryCatalog, Sigma, Variable};
+use std::collections::HashMap;
+
+pub(crate) const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// LSP `CompletionItemKind`s.
+const FUNCTION: u8 = 3;
+const VARIABLE: u8 = 6;
+const TYPE_PARAMETER: u8 = 25;
+
+/// An open template.
+#[derive(Default)]
+struct Document {
+  text: String,
+  /// the variables of the last version that parsed, by name with their
+  /// declaration, so the completion still works while typing.
+  variables: Vec<(String, String)>,
+  /// the macros of the last version that parsed, with the byte range of
+  /// their names.
+  macros: Vec<(String, usize, usize)>,
+}
+
+impl Document {
+  fn update(&mut self, text: &str) {
+    self.text = text.to_owned();
+    if let Ok(sigma) = template(text).parse() {
+      self.variables = sigma
+        .variables()
+        .into_iter()
+        .map(|var| (var.name.to_owned(), declaration(var)))
+        .collect();
+      self.macros = sigma
+        .macros()
+        .into_iter()
+        .map(|(name, span)| (name.to_owned(), span.start(), span.end()))
+        .collect();
+    }
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct Server {
+  documents: HashMap<String, Document>,
+  shut_down: bool,
+}
+
+impl Server {
+  /// did the client ask for a `shutdown`.
+  pub(crate) fn is_shut_down(&self) -> bool {
+    self.shut_down
+  }
+
+  /// the responses and the notifications to send back for that message.
+  pub(crate) fn handle(&mut self, message: Value) -> Vec<Value> {
+    let params = &message["params"];
+    match (message["method"].as_str(), message.get("id")) {
+      (Some(method), Some(id)) => {
+        let response = match self.request(method, params) {
+          Ok(result) => {
+            json!({ "jsonrpc": "2.0", "id": id, "result": result })
+          },
+          Err((code, message)) => error(id.clone(), code, &message),
+        };
+        vec![response]
+      },
+      (Some(method), None) => self.notification(method, params),
+      // the server sends no request, so there is no response to read.
+      (None, Some(_)) if message.get("result").is_some() => Vec::new(),
+      _ => vec![error(Value::Null, INVALID_REQUEST, "invalid request")],
+    }
+  }
+
+  fn request(
+    &mut self,
+    method: &str,
+    params: &Value,
+  ) -> Result<Value, (i64, String)> {
+    if self.shut_down {
+      return Err((INVALID_REQUEST, "the server is shut down".to_owned()));
+    }
+    let result = match method {
+      "initialize" => capabilities(),
+      "shutdown" => {
+        self.shut_down = true;
+        Value::Null
+      },
+      "textDocument/completion" => self.completion(params),
+      "textDocument/hover" => self.hover(params),
+      "textDocument/definition" => self.definition(params),
+      _ => {
+        let message = format!("unknown method `{}`", method);
+        return Err((METHOD_NOT_FOUND, message));
+      },
+    };
+    Ok(result)
+  }
+
+  fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+    let uri = params["textDocument"]["uri"].as_str().unwrap_or_default();
+    match method {
+      "textDocument/didOpen" => {
+        let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+        let document = self.documents.entry(uri.to_owned()).or_default();
+        document.update(text);
+        vec![diagnostics(uri, &document.text)]
+      },
+      // the whole text is sent, see `capabilities`.
+      "textDocument/didChange" => {
+        let changes = params["contentChanges"].as_array();
+        let text = changes.and_then(|changes| changes.last());
+        if let (Some(document), Some(text)) = (
+          self.documents.get_mut(uri),
+          text.and_then(|change| change["text"].as_str()),
+        ) {
+          document.update(text);
+        }
+        Vec::new()
+      },
+      "textDocument/didSave" => {
+        let document = match self.documents.get_mut(uri) {
+          Some(document) => document,
+          None => return Vec::new(),
+        };
+        if let Some(text) = params["text"].as_str() {
+          document.update(text);
+        }
+        vec![diagnostics(uri, &document.text)]
+      },
+      "textDocument/didClose" => {
+        self.documents.remove(uri);
+        vec![publish(uri, Vec::new())]
+      },
+      // like `initialized` or `$/cancelRequest`.
+      _ => Vec::new(),
+    }
+  }
+
+  /// the document and the byte offset of a `TextDocumentPositionParams`.
+  fn at(&self, params: &Value) -> Option<(&Document, usize)> {
+    let uri = params["textDocument"]["uri"].as_str()?;
+    let document = self.documents.get(uri)?;
+    let offset = offset(&document.text, &params["position"])?;
+    Some((document, offset))
+  }
+
+  /// the variables in a tag, the helpers after a pipe, the data types after
+  /// a `:` and the macros in a `{{> call }}`.
+  fn completion(&self, params: &Value) -> Value {
+    let (document, offset) = match self.at(params) {
+      Some(found) => found,
+      None => return json!([]),
+    };
+    let text = &document.text;
+    let open = match tag_at(text, offset) {
+      Some(open) => open,
+      None => return json!([]),
+    };
+    let (start, _) = word_at(text, offset);
+    let before = text[open + 2..start].trim_end();
+    let items: Vec<_> = if before.ends_with('|') || before.ends_with("|>") {
+      let sigma = Sigma::new("");
+      let functions = sigma.functions().into_iter().map(|f| (f.name(), f));
+      // the flat names of the built-in helpers are still valid, ex: `TRIM`.
+      functions
+        .chain(sigma.aliases())
+        .map(|(name, f)| {
+          let detail = format!("{} -> {}", f.input(), f.output());
+          item(name, FUNCTION, &detail)
+        })
+        .collect()
+    } else if before.ends_with(':') && !before.ends_with("::") {
+      let types = Sigma::new("").data_types();
+      types
+        .into_iter()
+        .map(|name| item(name, TYPE_PARAMETER, "data type"))
+        .collect()
+    } else if before == ">" {
+      let macros = document.macros.iter();
+      macros
+        .map(|(name, ..)| item(name, FUNCTION, "macro"))
+        .collect()
+    } else {
+      let variables = document.variables.iter();
+      variables
+        .map(|(name, declaration)| item(name, VARIABLE, declaration))
+        .collect()
+    };
+    Value::Array(items)
+  }
+
+  /// the declaration of the variable under the cursor.
+  fn hover(&self, params: &Value) -> Value {
+    let (document, offset) = match self.at(params) {
+      Some(found) => found,
+      None => return Value::Null,
+    };
+    let text = &document.text;
+    let (start, word) = word_at(text, offset);
+    let declaration = document
+      .variables
+      .iter()
+      .find(|(name, _)| tag_at(text, offset).is_some() && name == word);
+    match declaration {
+      Some((_, declaration)) => json!({
+        "contents": {
+          "kind": "markdown",
+          "value": format!("```sigma\n{}\n```", declaration),
+        },
+        "range": range(text, start, start + word.len()),
+      }),
+      None => Value::Null,
+    }
+  }
+
+  /// where the macro of a `{{> call }}` is defined.
+  fn definition(&self, params: &Value) -> Value {
+    let (document, offset) = match self.at(params) {
+      Some(found) => found,
+      None => return Value::Null,
+    };
+    let text = &document.text;
+    let is_call = tag_at(text, offset)
+      .is_some_and(|open| text[open + 2..].starts_with('>'));
+    let (_, word) = word_at(text, offset);
+    // the offsets could be of an older version while the template has errors.
+    let definition = document.macros.iter().find(|(name, start, end)| {
+      name == word && text.get(*start..*end) == Some(name.as_str())
+    });
+    match definition {
+      Some((_, start, end)) if is_call => json!({
+        "uri": params["textDocument"]["uri"],
+        "range": range(text, *start, *end),
+      }),
+      _ => Value::Null,
+    }
+  }
+}
+
+fn capabilities() -> Value {
+  json!({
+    "capabilities": {
+      // the whole text on change, with the text on save.
+      "textDocumentSync": {
+        "openClose": true,
+        "change": 1,
+        "save": { "includeText": true },
+      },
+      "completionProvider": { "triggerCharacters": ["{", "|", ":", " ", ">"] },
+      "hoverProvider": true,
+      "definitionProvider": true,
+    },
+    "serverInfo": { "name": "sigma-lsp", "version": env!("CARGO_PKG_VERSION") },
+  })
+}
+
+/// the template of that text, with an empty catalog that accepts any
+/// `t("key")` message, the real one is only known at render time.
+fn template(text: &str) -> Sigma<'_> {
+  Sigma::new(text).catalog(MemoryCatalog::new())
+}
+
+/// the `publishDiagnostics` notification with every error of the template.
+fn diagnostics(uri: &str, text: &str) -> Value {
+  let errors = match template(text).parse_all() {
+    Ok(_) => Vec::new(),
+    Err(errors) => errors,
+  };
+  let diagnostics = errors
+    .iter()
+    .map(|error| {
+      let (start, end) = match error.location {
+        InputLocation::Pos(pos) => (pos, pos),
+        InputLocation::Span(span) => span,
+      };
+      json!({
+        "range": range(text, start, end),
+        "severity": 1,
+        "source": "sigma",
+        "message": error.variant.message(),
+      })
+    })
+    .collect();
+  publish(uri, diagnostics)
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+  json!({
+    "jsonrpc": "2.0",
+    "method": "textDocument/publishDiagnostics",
+    "params": { "uri": uri, "diagnostics": diagnostics },
+  })
+}
+
+pub(crate) fn error(id: Value, code: i64, message: &str) -> Value {
+  json!({
+    "jsonrpc": "2.0",
+    "id": id,
+    "error": { "code": code, "message": message },
+  })
+}
+
+fn item(label: &str, kind: u8, detail: &str) -> Value {
+  json!({ "label": label, "kind": kind, "detail": detail })
+}
+
+/// the declaration of a variable as written in the template, ex:
+/// `age?: u8(1..=120)`.
+fn declaration(var: &Variable) -> String {
+  let mut declaration = var.name.to_owned();
+  if var.nullable {
+    declaration.push('?');
+  }
+  match &var.data_type {
+    Some((_, span)) if var.typed => {
+      declaration.push_str(": ");
+      declaration.push_str(span.as_str());
+    },
+    _ => return declaration,
+  }
+  if let Some((constraint, _)) = &var.constraint {
+    declaration.push_str(&format!("({})", constraint));
+  }
+  declaration
+}
+
+/// the start of the `{{` tag around that byte offset.
+fn tag_at(text: &str, offset: usize) -> Option<usize> {
+  let open = text[..offset].rfind("{{")?;
+  match text[open..offset].contains("}}") {
+    true => None,
+    false => Some(open),
+  }
+}
+
+/// the start and the name, or the helper, under that byte offset.
+fn word_at(text: &str, offset: usize) -> (usize, &str) {
+  let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+  let start = text[..offset]
+    .char_indices()
+    .rev()
+    .find(|(_, c)| !is_word(*c))
+    .map_or(0, |(i, c)| i + c.len_utf8());
+  let end = text[offset..]
+    .find(|c| !is_word(c))
+    .map_or(text.len(), |i| offset + i);
+  (start, &text[start..end])
+}
+
+fn range(text: &str, start: usize, end: usize) -> Value {
+  json!({ "start": position(text, start), "end": position(text, end) })
+}
+
+/// the LSP position of a byte offset, the characters are UTF-16 code units.
+fn position(text: &str, offset: usize) -> Value {
+  let before = &text[..offset];
+  let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+  json!({
+    "line": before.matches('\n').count(),
+    "character": before[line_start..].encode_utf16().count(),
+  })
+}
+
+/// the byte offset of an LSP position, past the end of a line is its end.
+fn offset(text: &str, position: &Value) -> Option<usize> {
+  let line = position["line"].as_u64()? as usize;
+  let character = position["character"].as_u64()? as usize;
+  let line_start = match line {
+    0 => 0,
+    _ => text.match_indices('\n').nth(line - 1)?.0 + 1,
+  };
+  let mut units = 0;
+  for (i, c) in text[line_start..].char_indices() {
+    if units >= character || c == '\n' {
+      return Some(line_start + i);
+    }
+    units += c.len_utf16();
+  }
+  Some(text.len())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn positions() {
+    let text = "é{{ a }}\n𝄞 {{ b }}";
+    let at = |line, character| json!({ "line": line, "character": character });
+    assert_eq!(offset(text, &at(0, 1)), Some(2));
+    assert_eq!(offset(text, &at(0, 99)), Some(9));
+    assert_eq!(offset(text, &at(1, 3)), Some(15));
+    assert_eq!(position(text, 15), at(1, 3));
+    assert_eq!(word_at(text, 5), (5, "a"));
+    assert_eq!(tag_at(text, 5), Some(2));
+    assert_eq!(tag_at(text, 14), None);
+  }
+}
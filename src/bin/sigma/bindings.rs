@@ -0,0 +1,207 @@
+//! Reading the bindings from the command line and the data files.
+use clap::ValueEnum;
+use serde_json::Value;
+use std::path::Path;
+
+/// The format of a data file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+  Json,
+  Yaml,
+  Toml,
+  Env,
+}
+
+impl Format {
+  /// guess the format from the file extension, `.env` files could be named
+  /// `.env` or `prod.env`.
+  pub(crate) fn guess(path: &Path) -> Option<Self> {
+    let name = path.file_name()?.to_str()?;
+    let extension = name.rsplit('.').next()?;
+    let format = match extension {
+      "json" => Format::Json,
+      "yaml" | "yml" => Format::Yaml,
+      "toml" => Format::Toml,
+      "env" => Format::Env,
+      _ => return None,
+    };
+    Some(format)
+  }
+}
+
+/// parse a `--set key=value` argument.
+pub(crate) fn parse_set(arg: &str) -> Result<(String, String), String> {
+  match arg.split_once('=') {
+    Some((key, value)) if !key.trim().is_empty() => {
+      Ok((key.trim().to_owned(), value.to_owned()))
+    },
+    _ => Err(format!("expected `KEY=VALUE` but got `{}`", arg)),
+  }
+}
+
+/// the bindings in a data file, only the top level strings, numbers and
+/// bools are used, a `null` leaves the variable unbinded.
+pub(crate) fn parse(
+  source: &str,
+  format: Format,
+) -> Result<Vec<(String, String)>, String> {
+  match format {
+    Format::Json => {
+      let value = serde_json::from_str(source).map_err(|e| e.to_string())?;
+      from_value(value)
+    },
+    Format::Yaml => {
+      let value = serde_yaml::from_str(source).map_err(|e| e.to_string())?;
+      from_value(value)
+    },
+    Format::Toml => from_toml(source),
+    Format::Env => from_env(source),
+  }
+}
+
+fn from_value(value: Value) -> Result<Vec<(String, String)>, String> {
+  let object = match value {
+    Value::Object(object) => object,
+    Value::Null => return Ok(Vec::new()),
+    _ => return Err("the data must be an object of bindings".to_owned()),
+  };
+  let mut bindings = Vec::new();
+  for (key, value) in object {
+    let value = match value {
+      Value::Null => continue,
+      Value::String(value) => value,
+      Value::Number(value) => value.to_string(),
+      Value::Bool(value) => value.to_string(),
+      Value::Array(_) | Value::Object(_) => return Err(unsupported(&key)),
+    };
+    bindings.push((key, value));
+  }
+  Ok(bindings)
+}
+
+fn from_toml(source: &str) -> Result<Vec<(String, String)>, String> {
+  use toml::Value;
+  let table: toml::Table = source.parse().map_err(|e| format!("{}", e))?;
+  let mut bindings = Vec::new();
+  for (key, value) in table {
+    let value = match value {
+      Value::String(value) => value,
+      Value::Integer(value) => value.to_string(),
+      Value::Float(value) => value.to_string(),
+      Value::Boolean(value) => value.to_string(),
+      Value::Datetime(value) => value.to_string(),
+      Value::Array(_) | Value::Table(_) => return Err(unsupported(&key)),
+    };
+    bindings.push((key, value));
+  }
+  Ok(bindings)
+}
+
+/// `KEY=value` lines, with `#` comments, an optional `export` and quoted
+/// values.
+fn from_env(source: &str) -> Result<Vec<(String, String)>, String> {
+  let mut bindings = Vec::new();
+  for (i, line) in source.lines().enumerate() {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let line = line.strip_prefix("export ").unwrap_or(line);
+    let (key, value) = line
+      .split_once('=')
+      .ok_or_else(|| format!("line {}: expected `KEY=value`", i + 1))?;
+    let value = value.trim();
+    let value = if let Some(quoted) = unquote(value, '"') {
+      unescape(quoted)
+    } else if let Some(quoted) = unquote(value, '\'') {
+      quoted.to_owned()
+    } else {
+      // an unquoted value could end with a comment.
+      value
+        .split(" #")
+        .next()
+        .unwrap_or(value)
+        .trim_end()
+        .to_owned()
+    };
+    bindings.push((key.trim().to_owned(), value));
+  }
+  Ok(bindings)
+}
+
+fn unquote(value: &str, quote: char) -> Option<&str> {
+  value.strip_prefix(quote)?.strip_suffix(quote)
+}
+
+/// the `\n`, `\"` and `\\` escapes of a double quoted value, the other
+/// backslashes are kept as is.
+fn unescape(quoted: &str) -> String {
+  let mut value = String::with_capacity(quoted.len());
+  let mut chars = quoted.chars();
+  while let Some(c) = chars.next() {
+    if c != '\\' {
+      value.push(c);
+      continue;
+    }
+    match chars.next() {
+      Some('n') => value.push('\n'),
+      Some(escaped @ ('"' | '\\')) => value.push(escaped),
+      Some(other) => {
+        value.push('\\');
+        value.push(other);
+      },
+      None => value.push('\\'),
+    }
+  }
+  value
+}
+
+fn unsupported(key: &str) -> String {
+  format!("`{}` must be a string, a number or a bool", key)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sorted(mut bindings: Vec<(String, String)>) -> Vec<(String, String)> {
+    bindings.sort();
+    bindings
+  }
+
+  fn pairs(expected: &[(&str, &str)]) -> Vec<(String, String)> {
+    let pairs = expected.iter().map(|(k, v)| (k.to_string(), v.to_string()));
+    sorted(pairs.collect())
+  }
+
+  #[test]
+  fn data_files() {
+    let expected = pairs(&[("name", "Ada"), ("age", "36"), ("admin", "true")]);
+    let json = r#"{"name": "Ada", "age": 36, "admin": true, "bio": null}"#;
+    assert_eq!(sorted(parse(json, Format::Json).unwrap()), expected);
+    let yaml = "name: Ada\nage: 36\nadmin: true\nbio: ~\n";
+    assert_eq!(sorted(parse(yaml, Format::Yaml).unwrap()), expected);
+    let toml = "name = \"Ada\"\nage = 36\nadmin = true\n";
+    assert_eq!(sorted(parse(toml, Format::Toml).unwrap()), expected);
+    let env = "# user\nexport name=\"Ada\"\nage = 36 # years\nadmin='true'\n";
+    assert_eq!(sorted(parse(env, Format::Env).unwrap()), expected);
+    let err = parse(r#"{"tags": ["a"]}"#, Format::Json).unwrap_err();
+    assert!(err.contains("`tags` must be a string"));
+    assert!(parse("[1]", Format::Json).is_err());
+    assert!(parse("name", Format::Env).unwrap_err().contains("line 1"));
+    // `\\n` is a backslash then `n`, not a backslash then a newline.
+    let env = "a=\"x\\\\ny\"\nb=\"1\\n\\\"2\\\" \\t\"\n";
+    let escaped = pairs(&[("a", "x\\ny"), ("b", "1\n\"2\" \\t")]);
+    assert_eq!(sorted(parse(env, Format::Env).unwrap()), escaped);
+  }
+
+  #[test]
+  fn arguments() {
+    let set = parse_set("greeting=a=b").unwrap();
+    assert_eq!(set, ("greeting".to_owned(), "a=b".to_owned()));
+    assert!(parse_set("=x").is_err());
+    assert_eq!(Format::guess(Path::new("a/b.yml")), Some(Format::Yaml));
+    assert_eq!(Format::guess(Path::new(".env")), Some(Format::Env));
+    assert_eq!(Format::guess(Path::new("data")), None);
+  }
+}
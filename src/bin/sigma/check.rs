@@ -0,0 +1,146 @@
+//! `sigma check`, parse many templates and report all their errors.
+use crate::{diagnostics, Failure};
+use clap::{Args, ValueEnum};
+use pest::{
+  error::{Error, LineColLocation},
+  RuleType,
+};
+use serde_json::{json, Value};
+use sigma::{MemoryCatalog, Sigma};
+use std::{fs, path::PathBuf};
+
+#[derive(Args)]
+pub(crate) struct Check {
+  /// the templates, the globs like `templates/**/*.sigma` are expanded.
+  #[arg(required = true, value_name = "FILES")]
+  patterns: Vec<String>,
+  /// how to report the errors, `json` prints them on stdout.
+  #[arg(long, value_enum, default_value_t = Report::Text)]
+  format: Report,
+}
+
+/// How to report the errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Report {
+  Text,
+  Json,
+}
+
+pub(crate) fn check(args: Check, color: bool) -> Result<(), Failure> {
+  let files = expand(&args.patterns)?;
+  let mut report = Vec::new();
+  let mut count = 0;
+  for file in &files {
+    let path = file.display().to_string();
+    let template = fs::read_to_string(file)
+      .map_err(|e| Failure::Other(format!("{}: {}", path, e)))?;
+    // an empty catalog accepts any `t("key")` message, the real one is only
+    // known at render time.
+    let sigma = Sigma::new(&template).catalog(MemoryCatalog::new());
+    let errors = match sigma.parse_all() {
+      Ok(_) => continue,
+      Err(errors) => errors,
+    };
+    count += errors.len();
+    for error in &errors {
+      match args.format {
+        Report::Text => eprint!("{}", diagnostics::render(error, &path, color)),
+        Report::Json => report.push(to_json(error, &path)),
+      }
+    }
+  }
+  match args.format {
+    Report::Text => eprintln!(
+      "checked {} {}, found {} {}",
+      files.len(),
+      plural(files.len(), "file"),
+      count,
+      plural(count, "error")
+    ),
+    Report::Json => println!("{}", Value::Array(report)),
+  }
+  match count {
+    0 => Ok(()),
+    _ => Err(Failure::Template),
+  }
+}
+
+/// the files matching the patterns, sorted and without duplicates.
+pub(crate) fn expand(patterns: &[String]) -> Result<Vec<PathBuf>, Failure> {
+  let mut files = Vec::new();
+  for pattern in patterns {
+    let paths = glob::glob(pattern).map_err(|e| {
+      Failure::Other(format!("invalid pattern `{}`: {}", pattern, e))
+    })?;
+    let before = files.len();
+    for path in paths {
+      let path = path.map_err(|e| Failure::Other(e.to_string()))?;
+      if path.is_file() {
+        files.push(path);
+      }
+    }
+    if files.len() == before {
+      return Err(Failure::Other(format!("no file matches `{}`", pattern)));
+    }
+  }
+  files.sort();
+  files.dedup();
+  Ok(files)
+}
+
+/// an error as `{ file, line, column, end_line, end_column, message }`, the
+/// lines and the columns start at 1.
+fn to_json<R: RuleType>(error: &Error<R>, path: &str) -> Value {
+  let ((line, column), (end_line, end_column)) = match error.line_col {
+    LineColLocation::Pos(start) => (start, start),
+    LineColLocation::Span(start, end) => (start, end),
+  };
+  json!({
+    "file": path,
+    "line": line,
+    "column": column,
+    "end_line": end_line,
+    "end_column": end_column,
+    "message": error.variant.message(),
+  })
+}
+
+fn plural(count: usize, word: &str) -> String {
+  match count {
+    1 => word.to_owned(),
+    _ => format!("{}s", word),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn json_report() {
+    let input = "{{ id: u9 }}\n{{ name | TRIMM }}";
+    let errors = Sigma::new(input).parse_all().unwrap_err();
+    let report: Vec<_> = errors.iter().map(|e| to_json(e, "a.sigma")).collect();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0]["file"], "a.sigma");
+    assert_eq!(
+      (&report[1]["line"], &report[1]["column"]),
+      (&json!(2), &json!(11))
+    );
+    let message = report[1]["message"].as_str().unwrap();
+    assert!(message.contains("did you mean: `TRIM`"), "{}", message);
+  }
+
+  #[test]
+  fn translated_messages() {
+    let path = std::env::temp_dir().join("sigma-check-messages.sigma");
+    fs::write(&path, "{{ t(\"hello\") }}").unwrap();
+    let args = Check {
+      patterns: vec![path.display().to_string()],
+      format: Report::Json,
+    };
+    let checked = check(args, false);
+    fs::remove_file(&path).unwrap();
+    assert!(checked.is_ok());
+  }
+}
@@ -0,0 +1,86 @@
+//! Printing the pest errors, with colours on a terminal.
+use clap::ValueEnum;
+use pest::{error::Error, RuleType};
+use std::io::{self, IsTerminal};
+
+/// When to use colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Color {
+  Auto,
+  Always,
+  Never,
+}
+
+impl Color {
+  /// should the errors written to stderr be coloured.
+  pub(crate) fn enabled(self) -> bool {
+    match self {
+      Color::Auto => {
+        io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+      },
+      Color::Always => true,
+      Color::Never => false,
+    }
+  }
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// the error as pest shows it, after an `error: path` header, the gutter is
+/// blue and the `^---^` markers are red.
+pub(crate) fn render<R: RuleType>(
+  error: &Error<R>,
+  path: &str,
+  color: bool,
+) -> String {
+  let error = error.clone().with_path(path);
+  let text = error.to_string();
+  if !color {
+    return format!("error: {}\n{}\n", path, text);
+  }
+  let mut output = format!("{}error{}: {}\n", RED, RESET, path);
+  for line in text.lines() {
+    let trimmed = line.trim_start();
+    if let Some(location) = trimmed.strip_prefix("-->") {
+      let indent = &line[..line.len() - trimmed.len()];
+      output.push_str(&format!("{}{}-->{}{}\n", indent, BLUE, RESET, location));
+    } else if let Some(message) = trimmed.strip_prefix("= ") {
+      let indent = &line[..line.len() - trimmed.len()];
+      output.push_str(&format!("{}{}= {}{}\n", indent, BOLD, message, RESET));
+    } else if let Some(bar) = line.find('|') {
+      let (gutter, rest) = line.split_at(bar + 1);
+      let rest = match rest.trim_start().starts_with('^') {
+        true => format!("{}{}{}", RED, rest, RESET),
+        false => rest.to_owned(),
+      };
+      output.push_str(&format!("{}{}{}{}\n", BLUE, gutter, RESET, rest));
+    } else {
+      output.push_str(line);
+      output.push('\n');
+    }
+  }
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use sigma::Sigma;
+  use std::collections::HashMap;
+
+  #[test]
+  fn colours() {
+    let sigma = Sigma::new("Hi {{ name }}").parse().unwrap();
+    let error = sigma.render(&HashMap::new()).unwrap_err();
+    let plain = render(&error, "hi.sigma", false);
+    assert!(plain.starts_with("error: hi.sigma\n --> hi.sigma:1:7"));
+    assert!(plain.contains("= unbinded variable: `name`"));
+    assert!(!plain.contains('\x1b'));
+    let colored = render(&error, "hi.sigma", true);
+    assert!(colored.contains("\x1b[1;34m1 |\x1b[0m Hi {{ name }}"));
+    assert!(colored.contains("\x1b[1;31m       ^--^\x1b[0m"));
+  }
+}
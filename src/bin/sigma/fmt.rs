@@ -0,0 +1,61 @@
+//! `sigma fmt`, format the templates in place.
+use crate::{check, diagnostics, Failure};
+use clap::Args;
+use sigma::Sigma;
+use std::{
+  fs,
+  io::{self, Write},
+  path::Path,
+};
+
+#[derive(Args)]
+pub(crate) struct Fmt {
+  /// the templates, the globs are expanded, `-` formats stdin to stdout.
+  #[arg(required = true, value_name = "FILES")]
+  patterns: Vec<String>,
+  /// do not write the files, list the unformatted ones and fail if any.
+  #[arg(long)]
+  check: bool,
+}
+
+pub(crate) fn fmt(args: Fmt, color: bool) -> Result<(), Failure> {
+  if args.patterns == ["-"] {
+    let template = crate::read(Path::new("-"))?;
+    let output = format(&template, "<stdin>", color)?;
+    if args.check && output != template {
+      eprintln!("<stdin> is not formatted");
+      return Err(Failure::Template);
+    }
+    if !args.check {
+      io::stdout().write_all(output.as_bytes())?;
+    }
+    return Ok(());
+  }
+  let mut unformatted = 0;
+  for file in check::expand(&args.patterns)? {
+    let path = file.display().to_string();
+    let template = crate::read(&file)?;
+    let output = format(&template, &path, color)?;
+    if output == template {
+      continue;
+    }
+    if args.check {
+      println!("{}", path);
+      unformatted += 1;
+    } else {
+      fs::write(&file, output)
+        .map_err(|e| Failure::Other(format!("{}: {}", path, e)))?;
+    }
+  }
+  match unformatted {
+    0 => Ok(()),
+    _ => Err(Failure::Template),
+  }
+}
+
+fn format(template: &str, path: &str, color: bool) -> Result<String, Failure> {
+  Sigma::new(template).format().map_err(|e| {
+    eprint!("{}", diagnostics::render(&e, path, color));
+    Failure::Template
+  })
+}
@@ -0,0 +1,168 @@
+//! The `sigma` command line, to use the templates without writing Rust.
+//!
+//! ```text
+//! sigma render hello.sigma --set name=Ada --data user.yaml -o hello.txt
+//! sigma check "templates/**/*.sigma" --format json
+//! sigma fmt --check "templates/**/*.sigma"
+//! ```
+mod bindings;
+mod check;
+mod diagnostics;
+mod fmt;
+
+use crate::{bindings::Format, check::Check, diagnostics::Color, fmt::Fmt};
+use clap::{Args, Parser, Subcommand};
+use sigma::{RenderOptions, Sigma};
+use std::{
+  collections::HashMap,
+  fs,
+  io::{self, Read, Write},
+  path::{Path, PathBuf},
+  process::ExitCode,
+};
+
+#[derive(Parser)]
+#[command(name = "sigma", version, about = "Sigma σ templates made simple")]
+struct Cli {
+  /// when to colour the errors.
+  #[arg(long, value_enum, default_value_t = Color::Auto, global = true)]
+  color: Color,
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// render a template with some bindings.
+  Render(Render),
+  /// parse the templates and report all their errors, no bindings needed.
+  Check(Check),
+  /// format the templates, the text around the tags is kept as is.
+  Fmt(Fmt),
+}
+
+#[derive(Args)]
+struct Render {
+  /// the template file, `-` to read it from stdin.
+  template: PathBuf,
+  /// bind a variable, could be repeated and wins over the data file.
+  #[arg(short, long = "set", value_name = "KEY=VALUE")]
+  set: Vec<String>,
+  /// read the bindings from a `.json`, `.yaml`, `.toml` or `.env` file, `-`
+  /// to read them from stdin.
+  #[arg(short, long, value_name = "FILE")]
+  data: Option<PathBuf>,
+  /// the format of the data file, guessed from its extension, `json` for
+  /// stdin.
+  #[arg(short, long, value_enum)]
+  format: Option<Format>,
+  /// write the output to that file instead of stdout.
+  #[arg(short, long, value_name = "FILE")]
+  output: Option<PathBuf>,
+  /// render in that locale, ex: `fr` or `pt-BR`.
+  #[arg(long)]
+  locale: Option<String>,
+  /// fail on any binding that is not used in the template.
+  #[arg(long)]
+  strict: bool,
+}
+
+/// Why the command failed.
+enum Failure {
+  /// the template has errors, already printed.
+  Template,
+  /// anything else, like a missing file.
+  Other(String),
+}
+
+impl From<io::Error> for Failure {
+  fn from(e: io::Error) -> Self {
+    Failure::Other(e.to_string())
+  }
+}
+
+fn main() -> ExitCode {
+  let cli = Cli::parse();
+  let color = cli.color.enabled();
+  let result = match cli.command {
+    Command::Render(args) => render(args, color),
+    Command::Check(args) => check::check(args, color),
+    Command::Fmt(args) => fmt::fmt(args, color),
+  };
+  match result {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(Failure::Template) => ExitCode::FAILURE,
+    Err(Failure::Other(message)) => {
+      eprintln!("sigma: {}", message);
+      ExitCode::from(2)
+    },
+  }
+}
+
+fn render(args: Render, color: bool) -> Result<(), Failure> {
+  let stdin = Path::new("-");
+  if args.template == stdin && args.data.as_deref() == Some(stdin) {
+    return Err(Failure::Other(
+      "the template and the data cannot both be read from stdin".to_owned(),
+    ));
+  }
+  let path = match args.template == stdin {
+    true => "<stdin>".to_owned(),
+    false => args.template.display().to_string(),
+  };
+  let template = read(&args.template)?;
+  let mut bindings = Vec::new();
+  if let Some(data) = &args.data {
+    let format = match args.format {
+      Some(format) => format,
+      None if data == stdin => Format::Json,
+      None => Format::guess(data).ok_or_else(|| {
+        Failure::Other(format!(
+          "cannot guess the format of `{}`, use `--format`",
+          data.display()
+        ))
+      })?,
+    };
+    let source = read(data)?;
+    bindings = bindings::parse(&source, format).map_err(|message| {
+      Failure::Other(format!("{}: {}", data.display(), message))
+    })?;
+  }
+  for set in &args.set {
+    bindings.push(bindings::parse_set(set).map_err(Failure::Other)?);
+  }
+  let bindings: HashMap<&str, &str> = bindings
+    .iter()
+    .map(|(key, value)| (key.as_str(), value.as_str()))
+    .collect();
+  let mut options = RenderOptions::new();
+  if let Some(locale) = &args.locale {
+    options = options.locale(locale);
+  }
+  if args.strict {
+    options = options.strict();
+  }
+  let output = Sigma::new(&template)
+    .parse()
+    .and_then(|sigma| sigma.render_with(&bindings, &options))
+    .map_err(|e| {
+      eprint!("{}", diagnostics::render(&e, &path, color));
+      Failure::Template
+    })?;
+  match &args.output {
+    Some(file) => fs::write(file, output)?,
+    None => io::stdout().write_all(output.as_bytes())?,
+  }
+  Ok(())
+}
+
+/// read a file, or stdin for `-`.
+fn read(path: &Path) -> Result<String, Failure> {
+  if path == Path::new("-") {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    return Ok(input);
+  }
+  fs::read_to_string(path)
+    .map_err(|e| Failure::Other(format!("{}: {}", path.display(), e)))
+}
@@ -0,0 +1,403 @@
+use crate::{parser::Rule, DataType, SigmaResult};
+use pest::{
+  error::{Error as PestError, ErrorVariant},
+  iterators::Pair,
+};
+use regex::Regex;
+use std::{fmt, str::FromStr};
+
+/// Refinement on top of a declared data type.
+#[derive(Clone, Debug)]
+pub enum Constraint {
+  /// The value must be in that range.
+  ///
+  /// ex: `{{ age: u8(18..=120) }}`
+  Range(Bounds),
+  /// The number of chars in the value must be in that range.
+  ///
+  /// ex: `{{ name: str(len 1..=40) }}`
+  Length(Bounds),
+  /// The value must match that regex.
+  ///
+  /// ex: `{{ code: str(/^[A-Z]{3}$/) }}`
+  Pattern(Regex),
+  /// The value must be one of the variants.
+  ///
+  /// ex: `{{ tier: enum(gold, silver) }}`
+  OneOf(Vec<String>),
+}
+
+/// A `start..end` or `start..=end` range, any of the two bounds could be
+/// omitted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bounds {
+  /// the lower bound, always inclusive.
+  pub start: Option<Number>,
+  /// the upper bound.
+  pub end: Option<Number>,
+  /// is the upper bound inclusive (`..=`)
+  pub inclusive: bool,
+}
+
+impl Bounds {
+  fn contains(&self, value: Number) -> bool {
+    self.start.is_none_or(|start| value >= start)
+      && self.end.is_none_or(|end| {
+        if self.inclusive {
+          value <= end
+        } else {
+          value < end
+        }
+      })
+  }
+}
+
+impl fmt::Display for Bounds {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let bound = |bound: Option<Number>| {
+      bound.map(|number| number.to_string()).unwrap_or_default()
+    };
+    write!(
+      f,
+      "{}..{}{}",
+      bound(self.start),
+      if self.inclusive { "=" } else { "" },
+      bound(self.end)
+    )
+  }
+}
+
+/// A bound of a range, in the widest type of its numeric data type.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Number {
+  /// a bound of a signed integer type.
+  Int(i128),
+  /// a bound of an unsigned integer type, or of a length.
+  Uint(u128),
+  /// a bound of a float type.
+  Float(f64),
+}
+
+// the bounds are never `NaN`, see the `bound` rule.
+impl Eq for Number {}
+
+impl Number {
+  /// the number of a value of that numeric data type, `None` for the other
+  /// data types.
+  fn parse(data_type: &DataType, data: &str) -> Option<Number> {
+    use crate::DataType::*;
+    match data_type {
+      I8 | I16 | I32 | I64 | I128 | Isize => data.parse().ok().map(Number::Int),
+      U8 | U16 | U32 | U64 | U128 | Usize => {
+        data.parse().ok().map(Number::Uint)
+      },
+      F32 | F64 => data.parse().ok().map(Number::Float),
+      _ => None,
+    }
+  }
+}
+
+impl fmt::Display for Number {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Number::Int(number) => write!(f, "{}", number),
+      Number::Uint(number) => write!(f, "{}", number),
+      // `{:?}` keeps the decimal point of `1.0`.
+      Number::Float(number) => write!(f, "{:?}", number),
+    }
+  }
+}
+
+// the patterns are compared by their source, `Regex` is not `PartialEq`.
+impl PartialEq for Constraint {
+  fn eq(&self, other: &Self) -> bool {
+    use self::Constraint::*;
+    match (self, other) {
+      (Range(a), Range(b)) | (Length(a), Length(b)) => a == b,
+      (Pattern(a), Pattern(b)) => a.as_str() == b.as_str(),
+      (OneOf(a), OneOf(b)) => a == b,
+      _ => false,
+    }
+  }
+}
+
+impl Eq for Constraint {}
+
+impl fmt::Display for Constraint {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Constraint::Range(bounds) => write!(f, "{}", bounds),
+      Constraint::Length(bounds) => write!(f, "len {}", bounds),
+      Constraint::Pattern(pattern) => write!(f, "/{}/", pattern),
+      Constraint::OneOf(variants) => write!(f, "{}", variants.join(", ")),
+    }
+  }
+}
+
+/// call `$f::<T>($args)` where `T` is the rust type of a numeric data type,
+/// or evaluate to `None` for other data types, see [`DataType::is_numeric`].
+macro_rules! with_numeric_type {
+  ($data_type:expr, $f:ident($($arg:expr),*)) => {{
+    use crate::DataType::*;
+    match $data_type {
+      U8 => Some($f::<u8>($($arg),*)),
+      I8 => Some($f::<i8>($($arg),*)),
+      U16 => Some($f::<u16>($($arg),*)),
+      I16 => Some($f::<i16>($($arg),*)),
+      U32 => Some($f::<u32>($($arg),*)),
+      I32 => Some($f::<i32>($($arg),*)),
+      U64 => Some($f::<u64>($($arg),*)),
+      I64 => Some($f::<i64>($($arg),*)),
+      U128 => Some($f::<u128>($($arg),*)),
+      I128 => Some($f::<i128>($($arg),*)),
+      Usize => Some($f::<usize>($($arg),*)),
+      Isize => Some($f::<isize>($($arg),*)),
+      F32 => Some($f::<f32>($($arg),*)),
+      F64 => Some($f::<f64>($($arg),*)),
+      _ => None,
+    }
+  }};
+}
+
+/// check that the bounds are valid values of `T` and that it's not an empty
+/// range.
+fn is_well_formed<T: FromStr + PartialOrd>(
+  start: Option<&str>,
+  end: Option<&str>,
+  inclusive: bool,
+) -> bool {
+  let parse = |bound: Option<&str>| match bound {
+    Some(bound) => bound.parse::<T>().ok().map(Some),
+    None => Some(None),
+  };
+  match (parse(start), parse(end)) {
+    (Some(Some(start)), Some(Some(end))) => {
+      if inclusive {
+        start <= end
+      } else {
+        start < end
+      }
+    },
+    (Some(None), Some(None)) => false,
+    // `..end` is empty when nothing is below `end`, like `..0` for a
+    // length, so `end - 1` is not a `T`.
+    (Some(None), Some(Some(_))) if !inclusive => {
+      match end.unwrap_or_default().parse::<i128>() {
+        Ok(end) => end
+          .checked_sub(1)
+          .is_some_and(|below| below.to_string().parse::<T>().is_ok()),
+        Err(_) => true,
+      }
+    },
+    (Some(_), Some(_)) => true,
+    _ => false,
+  }
+}
+
+/// the data types written as strings, the ones a length or a pattern makes
+/// sense for.
+fn is_str_like(data_type: &DataType) -> bool {
+  use crate::DataType::*;
+  matches!(
+    data_type,
+    Str
+      | NonEmptyStr
+      | Char
+      | Email
+      | Url
+      | Uuid
+      | Ipv4
+      | Ipv6
+      | Date
+      | DateTime
+      | Custom(_)
+  )
+}
+
+/// parse the `constraint` rule and make sure it makes sense for the declared
+/// data type.
+pub(crate) fn parse_constraint<'a>(
+  data_type: &DataType,
+  pair: Pair<'a, Rule>,
+) -> SigmaResult<'a, Constraint> {
+  let span = pair.as_span();
+  let error = |message: String| {
+    PestError::new_from_span(ErrorVariant::CustomError { message }, span)
+  };
+  let inner = pair.into_inner().next().unwrap();
+  let constraint = match inner.as_rule() {
+    Rule::bounds => {
+      if !data_type.is_numeric() {
+        return Err(error(format!(
+          "range constraint is only for numbers, not `{}`",
+          data_type
+        )));
+      }
+      let raw = inner.as_str();
+      match parse_bounds(data_type, inner) {
+        Some(bounds) => Constraint::Range(bounds),
+        None => {
+          return Err(error(format!(
+            "invalid range `{}` for `{}`",
+            raw, data_type
+          )))
+        },
+      }
+    },
+    Rule::length => {
+      let inner = inner.into_inner().next().unwrap();
+      if !is_str_like(data_type) {
+        return Err(error(format!(
+          "length constraint is only for strings, not `{}`",
+          data_type
+        )));
+      }
+      let raw = inner.as_str();
+      match parse_bounds(&DataType::Usize, inner) {
+        Some(bounds) => Constraint::Length(bounds),
+        None => {
+          return Err(error(format!("invalid length range `{}`", raw)));
+        },
+      }
+    },
+    Rule::pattern => {
+      let raw = inner.as_str();
+      let pattern = raw[1..raw.len() - 1].replace("\\/", "/");
+      if !is_str_like(data_type) {
+        return Err(error(format!(
+          "pattern constraint is only for strings, not `{}`",
+          data_type
+        )));
+      }
+      match Regex::new(&pattern) {
+        Ok(regex) => Constraint::Pattern(regex),
+        Err(e) => {
+          return Err(error(format!("invalid pattern `{}`: {}", raw, e)));
+        },
+      }
+    },
+    Rule::variants => {
+      if *data_type != DataType::Enum {
+        return Err(error(format!(
+          "variants are only for `enum`, not `{}`",
+          data_type
+        )));
+      }
+      let mut variants: Vec<String> = Vec::new();
+      for variant in inner.into_inner() {
+        if variants.iter().any(|v| v == variant.as_str()) {
+          return Err(PestError::new_from_span(
+            ErrorVariant::CustomError {
+              message: format!("duplicated variant `{}`", variant.as_str()),
+            },
+            variant.as_span(),
+          ));
+        }
+        variants.push(variant.as_str().to_owned());
+      }
+      Constraint::OneOf(variants)
+    },
+    _ => unreachable!(),
+  };
+  Ok(constraint)
+}
+
+/// the bounds of a numeric data type, `None` when a bound is not a value of
+/// that data type or when the range is empty.
+fn parse_bounds(data_type: &DataType, pair: Pair<Rule>) -> Option<Bounds> {
+  let (mut start, mut end, mut inclusive) = (None, None, false);
+  for part in pair.into_inner() {
+    match part.as_rule() {
+      Rule::bound_start => start = Some(part.as_str()),
+      Rule::bound_end => end = Some(part.as_str()),
+      Rule::range_op => inclusive = part.as_str() == "..=",
+      _ => {},
+    }
+  }
+  if with_numeric_type!(data_type, is_well_formed(start, end, inclusive))? {
+    let number = |bound: Option<&str>| match bound {
+      Some(bound) => Number::parse(data_type, bound).map(Some),
+      None => Some(None),
+    };
+    Some(Bounds {
+      start: number(start)?,
+      end: number(end)?,
+      inclusive,
+    })
+  } else {
+    None
+  }
+}
+
+/// check the value against the constraint, returns the error message on
+/// failure.
+///
+/// the value is already validated against its data type.
+pub(crate) fn check_constraint(
+  data_type: &DataType,
+  constraint: &Constraint,
+  data: &str,
+) -> Result<(), String> {
+  match constraint {
+    Constraint::Range(bounds) => {
+      let value = Number::parse(data_type, data);
+      if value.is_some_and(|value| !bounds.contains(value)) {
+        return Err(format!("is out of range `{}`", constraint));
+      }
+    },
+    Constraint::Length(bounds) => {
+      let length = Number::Uint(data.chars().count() as u128);
+      if !bounds.contains(length) {
+        return Err(format!("has a length out of range `{}`", constraint));
+      }
+    },
+    Constraint::Pattern(regex) => {
+      if !regex.is_match(data) {
+        return Err(format!("does not match `{}`", constraint));
+      }
+    },
+    Constraint::OneOf(variants) => {
+      if !variants.iter().any(|v| v == data) {
+        let mut extra_help = String::new();
+        if let Some(matches) = crate::parser::did_you_mean(data, variants) {
+          extra_help = format!(" did you mean: `{}` ?", matches);
+        }
+        return Err(format!("is not one of `{}`{}", constraint, extra_help));
+      }
+    },
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn bounds_contains() {
+    use self::Number::*;
+    let b = Bounds {
+      start: Some(Uint(18)),
+      end: Some(Uint(120)),
+      inclusive: true,
+    };
+    assert!(b.contains(Uint(18)) && b.contains(Uint(120)));
+    assert!(!b.contains(Uint(17)));
+    let b = Bounds {
+      start: None,
+      end: Some(Float(1.5)),
+      inclusive: false,
+    };
+    assert!(b.contains(Float(-3.0)) && !b.contains(Float(1.5)));
+    assert_eq!(b.to_string(), "..1.5");
+  }
+
+  #[test]
+  fn bounds_well_formed() {
+    assert!(is_well_formed::<u8>(Some("1"), Some("1"), true));
+    assert!(!is_well_formed::<u8>(Some("1"), Some("1"), false));
+    assert!(!is_well_formed::<u8>(Some("-1"), None, false));
+    assert!(!is_well_formed::<u8>(Some("0"), Some("256"), true));
+    assert!(!is_well_formed::<u8>(None, None, false));
+  }
+}
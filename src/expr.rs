@@ -0,0 +1,1119 @@
+use crate::{
+  i18n::DEFAULT_LOCALE,
+  parser::{self, Rule},
+  preview, unbinded_error, DataType, FunctionCall, RenderOptions, Sigma,
+  SigmaResult, Unbinded, Variable,
+};
+use pest::{
+  error::{Error as PestError, ErrorVariant},
+  iterators::Pair,
+  Span,
+};
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+/// A placeholder that holds an expression, like `{{ price * qty: f64 }}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Expression<'a> {
+  pub(crate) expr: Expr<'a>,
+  /// the declared data type of the result and where it was declared.
+  pub(crate) data_type: Option<(DataType, Span<'a>)>,
+  /// the helper functions pipeline, in the order they will be called
+  pub(crate) functions: Vec<FunctionCall<'a>>,
+  /// the data type of the value after the functions pipeline.
+  pub(crate) output: DataType,
+  /// the raw `{{ ... }}` pair as written in the template
+  pub(crate) pair_str: &'a str,
+}
+
+/// A `{{# let name = expr }}` or `{{# set name = expr }}` tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Let<'a> {
+  /// where the value is stored while rendering, see `Sigma::locals`.
+  slot: usize,
+  data_type: DataType,
+  name_span: Span<'a>,
+  pub(crate) expression: Expression<'a>,
+}
+
+/// A local variable in scope while parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Local<'a> {
+  pub(crate) name: &'a str,
+  pub(crate) slot: usize,
+  pub(crate) data_type: DataType,
+}
+
+/// The values of the local variables while rendering, by their slots, a
+/// local has no value when its expression needs a missing nullable value.
+pub(crate) type Locals = Vec<Option<Value>>;
+
+/// A type checked expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Expr<'a> {
+  kind: ExprKind<'a>,
+  pub(crate) data_type: DataType,
+  pub(crate) span: Span<'a>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ExprKind<'a> {
+  /// the index of the variable in `Sigma::vars`.
+  Var(usize),
+  /// the slot of a local variable.
+  Local(usize),
+  /// a string, number or bool, strings are already unescaped.
+  Literal(String),
+  Unary(&'a str, Box<Expr<'a>>),
+  Binary(&'a str, Box<Expr<'a>>, Box<Expr<'a>>),
+  /// `cond ? then : else`
+  Ternary(Box<Expr<'a>>, Box<Expr<'a>>, Box<Expr<'a>>),
+  /// `t("key", name)`, the message key and the arguments by their names.
+  Message(String, Vec<(&'a str, Expr<'a>)>),
+}
+
+/// Why the evaluation of an expression stopped.
+pub(crate) enum Halt {
+  /// the variable at that index in `Sigma::vars` has no binded value.
+  Unbinded(usize),
+  /// a local variable has no value.
+  Empty,
+  Error(PestError<Rule>),
+}
+
+impl From<PestError<Rule>> for Halt {
+  fn from(e: PestError<Rule>) -> Self {
+    Halt::Error(e)
+  }
+}
+
+/// The value of an expression while it's evaluated.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+  Int(i128),
+  Float(f64),
+  Bool(bool),
+  Str(String),
+}
+
+/// What the type checker knows about the placeholder being parsed.
+pub(crate) struct Placeholder<'s, 'd> {
+  /// the variables by their names, see `Sigma::declarations`.
+  declared: &'d mut HashMap<&'s str, Variable<'s>>,
+  /// the local variables in scope, the last one shadows the others.
+  locals: &'d [Local<'s>],
+  location: (usize, usize),
+  pair_str: &'s str,
+}
+
+impl<'s, 'd> Placeholder<'s, 'd> {
+  pub(crate) fn new(
+    pair: &Pair<'s, Rule>,
+    declared: &'d mut HashMap<&'s str, Variable<'s>>,
+    locals: &'d [Local<'s>],
+  ) -> Self {
+    let span = pair.as_span();
+    Self {
+      declared,
+      locals,
+      location: (span.start(), span.end()),
+      pair_str: pair.as_str(),
+    }
+  }
+}
+
+/// the local variable with that name.
+pub(crate) fn find_local<'a, 's>(
+  locals: &'a [Local<'s>],
+  name: &str,
+) -> Option<&'a Local<'s>> {
+  locals.iter().rev().find(|local| local.name == name)
+}
+
+impl<'s> Sigma<'s> {
+  /// parse and type check an `expr_pair`, the variables used in the
+  /// expression are added to `Sigma::vars`.
+  pub(crate) fn parse_expr_pair(
+    &mut self,
+    pair: Pair<'s, Rule>,
+    declared: &mut HashMap<&'s str, Variable<'s>>,
+    locals: &[Local<'s>],
+  ) -> SigmaResult<'s, Expression<'s>> {
+    let mut cx = Placeholder::new(&pair, declared, locals);
+    self.parse_expression(pair.into_inner().collect(), None, &mut cx)
+  }
+
+  /// parse a `{{ local }}` placeholder of a local variable, it's an
+  /// expression, so it's not one of the template variables.
+  pub(crate) fn parse_local_pair(
+    &mut self,
+    pair: Pair<'s, Rule>,
+    declared: &mut HashMap<&'s str, Variable<'s>>,
+    locals: &[Local<'s>],
+  ) -> SigmaResult<'s, Expression<'s>> {
+    let mut cx = Placeholder::new(&pair, declared, locals);
+    let mut pairs = Vec::new();
+    for pair in pair.into_inner() {
+      if pair.as_rule() != Rule::var {
+        pairs.push(pair);
+        continue;
+      }
+      for part in pair.into_inner() {
+        match part.as_rule() {
+          Rule::nullable | Rule::constraint => {
+            return Err(error(
+              "a local variable cannot be nullable or have a constraint"
+                .to_owned(),
+              part.as_span(),
+            ))
+          },
+          _ => pairs.push(part),
+        }
+      }
+    }
+    self.parse_expression(pairs, None, &mut cx)
+  }
+
+  /// parse a `{{# let name = expr }}` or `{{# set name = expr }}` pair, the
+  /// `let` adds a local variable to the scope.
+  pub(crate) fn parse_let_pair(
+    &mut self,
+    pair: Pair<'s, Rule>,
+    declared: &mut HashMap<&'s str, Variable<'s>>,
+    scope: &mut Vec<Local<'s>>,
+  ) -> SigmaResult<'s, Let<'s>> {
+    let mut cx = Placeholder::new(&pair, declared, scope);
+    let mut inner = pair.into_inner();
+    let _open = inner.next().unwrap();
+    let keyword = inner.next().unwrap().as_str();
+    let name = inner.next().unwrap();
+    let local = match keyword {
+      "set" => match find_local(cx.locals, name.as_str()) {
+        Some(local) => Some(local.clone()),
+        None => {
+          let names = cx.locals.iter().map(|local| local.name);
+          let extra_help = parser::did_you_mean(name.as_str(), names)
+            .map(|matches| format!(" did you mean: `{}` ?", matches))
+            .unwrap_or_default();
+          return Err(error(
+            format!(
+              "cannot set `{}`, it's not a local variable, use \
+               `{{{{# let {} = ... }}}}` to define it{}",
+              name.as_str(),
+              name.as_str(),
+              extra_help
+            ),
+            name.as_span(),
+          ));
+        },
+      },
+      _ => None,
+    };
+    let expected = local
+      .as_ref()
+      .map(|local| (local.data_type.clone(), name.as_span()));
+    let expression =
+      self.parse_expression(inner.collect(), expected, &mut cx)?;
+    let local = match local {
+      Some(local) => {
+        let (output, data_type) = (&expression.output, &local.data_type);
+        let numeric = output.is_numeric() && data_type.is_numeric();
+        if !numeric && !output.is_assignable_to(data_type) {
+          return Err(error(
+            format!(
+              "mismatched types: `{}` is `{}` but the value is `{}`",
+              local.name, data_type, output
+            ),
+            name.as_span(),
+          ));
+        }
+        local
+      },
+      None => {
+        self.locals += 1;
+        let local = Local {
+          name: name.as_str(),
+          slot: self.locals - 1,
+          data_type: expression.output.clone(),
+        };
+        scope.push(local.clone());
+        local
+      },
+    };
+    Ok(Let {
+      slot: local.slot,
+      data_type: local.data_type,
+      name_span: name.as_span(),
+      expression,
+    })
+  }
+
+  /// type check an expression, `pairs` has the `expr` itself and its
+  /// optional data type and helper functions.
+  fn parse_expression(
+    &mut self,
+    pairs: Vec<Pair<'s, Rule>>,
+    expected: Option<(DataType, Span<'s>)>,
+    cx: &mut Placeholder<'s, '_>,
+  ) -> SigmaResult<'s, Expression<'s>> {
+    let mut expr = None;
+    let mut data_type = expected;
+    let mut functions = Vec::new();
+    for pair in pairs {
+      match pair.as_rule() {
+        Rule::expr | Rule::var_name => expr = Some(pair),
+        Rule::data_type => {
+          data_type = Some((self.parse_data_type(&pair)?, pair.as_span()));
+        },
+        Rule::function => functions.push(pair),
+        _ => {},
+      }
+    }
+    let hint = data_type.as_ref().map(|(data_type, _)| data_type);
+    let expr = self.check_expr(expr.unwrap(), hint, cx)?;
+    let mut current_type = expr.data_type.clone();
+    if let Some((declared, span)) = &data_type {
+      let numeric = current_type.is_numeric() && declared.is_numeric();
+      if !numeric && !current_type.is_assignable_to(declared) {
+        return Err(error(
+          format!(
+            "mismatched types: expected `{}` but the expression is `{}`",
+            declared, current_type
+          ),
+          *span,
+        ));
+      }
+      current_type = declared.clone();
+    }
+    let mut calls = Vec::new();
+    for pair in functions {
+      let (call, output) = self.parse_function_call(pair, &current_type)?;
+      current_type = output;
+      calls.push(call);
+    }
+    Ok(Expression {
+      expr,
+      data_type,
+      functions: calls,
+      output: current_type,
+      pair_str: cx.pair_str,
+    })
+  }
+
+  /// type check an expression pair, `hint` is the data type expected by the
+  /// outer expression, it's used for the untyped variables and the numbers.
+  pub(crate) fn check_expr(
+    &mut self,
+    pair: Pair<'s, Rule>,
+    hint: Option<&DataType>,
+    cx: &mut Placeholder<'s, '_>,
+  ) -> SigmaResult<'s, Expr<'s>> {
+    use crate::DataType::*;
+    let span = pair.as_span();
+    let rule = pair.as_rule();
+    let mut inner: Vec<_> = pair.into_inner().collect();
+    if rule == Rule::message {
+      return self.check_message(span, inner, cx);
+    }
+    if inner.len() == 1 {
+      return self.check_expr(inner.remove(0), hint, cx);
+    }
+    let expr = match rule {
+      Rule::string => Expr {
+        kind: ExprKind::Literal(parser::unescape(span.as_str())),
+        data_type: Str,
+        span,
+      },
+      Rule::boolean => Expr {
+        kind: ExprKind::Literal(span.as_str().to_owned()),
+        data_type: Bool,
+        span,
+      },
+      Rule::number => {
+        let number = span.as_str();
+        let data_type = match hint {
+          // a `u128` literal could be too large for `Value::Int`.
+          Some(hint)
+            if hint.is_numeric()
+              && self.is_valid(hint, number)
+              && Value::parse(hint, number).is_some() =>
+          {
+            hint.clone()
+          },
+          _ if number.contains('.') => F64,
+          _ if number.parse::<i64>().is_ok() => I64,
+          _ if number.parse::<i128>().is_ok() => I128,
+          _ => {
+            return Err(error(
+              format!("number `{}` is too large", number),
+              span,
+            ))
+          },
+        };
+        Expr {
+          kind: ExprKind::Literal(number.to_owned()),
+          data_type,
+          span,
+        }
+      },
+      Rule::var_name => self.check_var(span, hint, cx)?,
+      Rule::ternary => self.check_ternary(span, inner, hint, cx)?,
+      Rule::unary => {
+        let operand = inner.pop().unwrap();
+        let mut expr = match inner.last().unwrap().as_str() {
+          "!" => self.check_expr(operand, Some(&Bool), cx)?,
+          _ => self.check_expr(operand, hint, cx)?,
+        };
+        for op in inner.into_iter().rev() {
+          let expected = match op.as_str() {
+            "!" if expr.data_type == Bool => None,
+            "!" => Some("`Bool`"),
+            _ if expr.data_type.is_numeric() => None,
+            _ => Some("numbers"),
+          };
+          if let Some(expected) = expected {
+            return Err(mismatched(op.as_str(), expected, &expr));
+          }
+          let span = join(self.input, op.as_span(), expr.span);
+          expr = Expr {
+            data_type: expr.data_type.clone(),
+            kind: ExprKind::Unary(op.as_str(), Box::new(expr)),
+            span,
+          };
+        }
+        expr
+      },
+      _ => self.check_binary(rule, inner, hint, cx)?,
+    };
+    Ok(expr)
+  }
+
+  /// type check a chain of binary operations of the same precedence, like
+  /// `a + b - c`.
+  fn check_binary(
+    &mut self,
+    rule: Rule,
+    pairs: Vec<Pair<'s, Rule>>,
+    hint: Option<&DataType>,
+    cx: &mut Placeholder<'s, '_>,
+  ) -> SigmaResult<'s, Expr<'s>> {
+    use crate::DataType::*;
+    let operands: Vec<_> = pairs.iter().step_by(2).collect();
+    // the data type the operands are checked against.
+    let operand_hint = match rule {
+      Rule::or_expr | Rule::and_expr => Some(Bool),
+      Rule::concat => Some(Str),
+      Rule::comparison => {
+        let known = operands.iter().find_map(|pair| self.synthesize(pair, cx));
+        let number = operands.iter().any(|pair| is_number(pair));
+        known.or(if number { Some(F64) } else { Some(Str) })
+      },
+      _ => operands
+        .iter()
+        .find_map(|pair| self.synthesize(pair, cx))
+        .or_else(|| hint.filter(|hint| hint.is_numeric()).cloned()),
+    };
+    let mut pairs = pairs.into_iter();
+    let mut left =
+      self.check_expr(pairs.next().unwrap(), operand_hint.as_ref(), cx)?;
+    while let (Some(op), Some(right)) = (pairs.next(), pairs.next()) {
+      let right = self.check_expr(right, operand_hint.as_ref(), cx)?;
+      let op = op.as_str();
+      let data_type = match rule {
+        Rule::or_expr | Rule::and_expr => {
+          for operand in &[&left, &right] {
+            if operand.data_type != Bool {
+              return Err(mismatched(op, "`Bool`", operand));
+            }
+          }
+          Bool
+        },
+        Rule::concat => Str,
+        Rule::comparison => {
+          let (l, r) = (&left.data_type, &right.data_type);
+          let comparable = (l.is_numeric() && r.is_numeric())
+            || (is_text(l) && is_text(r))
+            || (*l == Bool && *r == Bool && (op == "==" || op == "!="));
+          if !comparable {
+            return Err(error(
+              format!("mismatched types: cannot compare `{}` with `{}`", l, r),
+              join(self.input, left.span, right.span),
+            ));
+          }
+          Bool
+        },
+        _ => {
+          for operand in &[&left, &right] {
+            if !operand.data_type.is_numeric() {
+              let mut expected = "numbers".to_owned();
+              if op == "+" && is_text(&operand.data_type) {
+                expected.push_str(", use `~` to join strings");
+              }
+              return Err(mismatched(op, &expected, operand));
+            }
+          }
+          arithmetic_type(&left.data_type, &right.data_type)
+        },
+      };
+      let span = join(self.input, left.span, right.span);
+      left = Expr {
+        kind: ExprKind::Binary(op, Box::new(left), Box::new(right)),
+        data_type,
+        span,
+      };
+    }
+    Ok(left)
+  }
+
+  /// type check `cond ? then : else`, the condition must be a `bool` and
+  /// both of the branches must have the same kind of data type.
+  fn check_ternary(
+    &mut self,
+    span: Span<'s>,
+    pairs: Vec<Pair<'s, Rule>>,
+    hint: Option<&DataType>,
+    cx: &mut Placeholder<'s, '_>,
+  ) -> SigmaResult<'s, Expr<'s>> {
+    use crate::DataType::*;
+    let mut pairs = pairs.into_iter();
+    let (cond, then, otherwise) = (
+      pairs.next().unwrap(),
+      pairs.next().unwrap(),
+      pairs.next().unwrap(),
+    );
+    let cond = self.check_expr(cond, Some(&Bool), cx)?;
+    if cond.data_type != Bool {
+      return Err(mismatched("?", "`Bool`", &cond));
+    }
+    let branch_hint = hint
+      .cloned()
+      .or_else(|| self.synthesize(&then, cx))
+      .or_else(|| self.synthesize(&otherwise, cx));
+    let then = self.check_expr(then, branch_hint.as_ref(), cx)?;
+    let otherwise = self.check_expr(otherwise, branch_hint.as_ref(), cx)?;
+    let (t, o) = (&then.data_type, &otherwise.data_type);
+    let data_type = if t == o {
+      t.clone()
+    } else if t.is_numeric() && o.is_numeric() {
+      arithmetic_type(t, o)
+    } else if is_text(t) && is_text(o) {
+      Str
+    } else {
+      return Err(error(
+        format!(
+          "mismatched types: the branches are `{}` and `{}`, they must be \
+           of the same data type",
+          t, o
+        ),
+        join(self.input, then.span, otherwise.span),
+      ));
+    };
+    Ok(Expr {
+      kind: ExprKind::Ternary(
+        Box::new(cond),
+        Box::new(then),
+        Box::new(otherwise),
+      ),
+      data_type,
+      span,
+    })
+  }
+
+  /// type check a `t("key", args..)` message, the key must be one of the
+  /// catalog keys when the catalog knows them.
+  fn check_message(
+    &mut self,
+    span: Span<'s>,
+    inner: Vec<Pair<'s, Rule>>,
+    cx: &mut Placeholder<'s, '_>,
+  ) -> SigmaResult<'s, Expr<'s>> {
+    let catalog = match &self.catalog {
+      Some(catalog) => catalog.0.clone(),
+      None => {
+        return Err(error(
+          "`t` needs a message catalog, see `Sigma::catalog`".to_owned(),
+          span,
+        ))
+      },
+    };
+    let mut inner = inner.into_iter();
+    let key_pair = inner.next().unwrap();
+    let key = parser::unescape(key_pair.as_str());
+    let keys = catalog.keys();
+    if !keys.is_empty() && !keys.contains(&key.as_str()) {
+      let extra_help = parser::did_you_mean(&key, keys)
+        .map(|matches| format!(" did you mean: `{}` ?", matches))
+        .unwrap_or_default();
+      return Err(error(
+        format!("undefined message: `{}`{}", key, extra_help),
+        key_pair.as_span(),
+      ));
+    }
+    let mut args = Vec::new();
+    for arg in inner {
+      let mut parts: Vec<_> = arg.into_inner().collect();
+      let value = parts.pop().unwrap();
+      let name = match parts.pop() {
+        Some(name) => name.as_str(),
+        None if is_leaf(&value, Rule::var_name) => value.as_str(),
+        None => {
+          return Err(error(
+            "a message argument must be a variable or be named, ex: \
+             `count = n + 1`"
+              .to_owned(),
+            value.as_span(),
+          ))
+        },
+      };
+      // the arguments are formatted in as text.
+      let value = self.check_expr(value, Some(&DataType::Str), cx)?;
+      args.push((name, value));
+    }
+    Ok(Expr {
+      kind: ExprKind::Message(key, args),
+      data_type: DataType::Str,
+      span,
+    })
+  }
+
+  /// resolve a variable used in an expression, an undeclared variable takes
+  /// the data type of the `hint`.
+  fn check_var(
+    &mut self,
+    span: Span<'s>,
+    hint: Option<&DataType>,
+    cx: &mut Placeholder<'s, '_>,
+  ) -> SigmaResult<'s, Expr<'s>> {
+    let name = span.as_str();
+    if let Some(local) = find_local(cx.locals, name) {
+      return Ok(Expr {
+        kind: ExprKind::Local(local.slot),
+        data_type: local.data_type.clone(),
+        span,
+      });
+    }
+    let declared = cx.declared.entry(name).or_insert_with(|| Variable {
+      name,
+      ..Default::default()
+    });
+    if declared.data_type.is_none() {
+      match hint {
+        Some(hint) => declared.data_type = Some((hint.clone(), span)),
+        None => {
+          return Err(error(
+            format!(
+              "cannot infer the data type of `{}`, consider adding a data \
+               type for it, ex: `{{{{ {}: f64 }}}}`",
+              name, name
+            ),
+            span,
+          ))
+        },
+      }
+    }
+    let (data_type, type_span) = declared.data_type.clone().unwrap();
+    self.vars.push(Variable {
+      name,
+      nullable: declared.nullable,
+      data_type: Some((data_type.clone(), type_span)),
+      constraint: declared.constraint.clone(),
+      location: cx.location,
+      name_span: Some(span),
+      pair_str: cx.pair_str,
+      ..Default::default()
+    });
+    Ok(Expr {
+      kind: ExprKind::Var(self.vars.len() - 1),
+      data_type,
+      span,
+    })
+  }
+
+  /// the data type of an expression pair if it's known without a hint,
+  /// numbers and untyped variables has no data type on their own.
+  fn synthesize(
+    &self,
+    pair: &Pair<'s, Rule>,
+    cx: &Placeholder<'s, '_>,
+  ) -> Option<DataType> {
+    use crate::DataType::*;
+    let inner: Vec<_> = pair.clone().into_inner().collect();
+    if inner.len() == 1 {
+      return self.synthesize(&inner[0], cx);
+    }
+    match pair.as_rule() {
+      Rule::string | Rule::concat => Some(Str),
+      Rule::boolean | Rule::comparison | Rule::and_expr | Rule::or_expr => {
+        Some(Bool)
+      },
+      Rule::number => None,
+      Rule::var_name => match find_local(cx.locals, pair.as_str()) {
+        Some(local) => Some(local.data_type.clone()),
+        None => cx
+          .declared
+          .get(pair.as_str())
+          .and_then(|var| var.data_type.as_ref())
+          .map(|(data_type, _)| data_type.clone()),
+      },
+      Rule::unary => self.synthesize(inner.last().unwrap(), cx),
+      Rule::ternary => self
+        .synthesize(&inner[1], cx)
+        .or_else(|| self.synthesize(&inner[2], cx)),
+      _ => {
+        let mut types = inner.iter().step_by(2).map(|p| self.synthesize(p, cx));
+        let first = types.next().unwrap();
+        types.fold(first, |left, right| match (left, right) {
+          (Some(l), Some(r)) => Some(arithmetic_type(&l, &r)),
+          (l, r) => l.or(r),
+        })
+      },
+    }
+  }
+
+  /// render an expression placeholder, a missing value is handled the same
+  /// way as a missing variable in a `{{ var }}` placeholder.
+  pub(crate) fn render_expression(
+    &self,
+    expression: &Expression<'s>,
+    bindings: &HashMap<&str, &str>,
+    options: &RenderOptions,
+    locals: &Locals,
+    output: &mut String,
+  ) -> SigmaResult<'s, ()> {
+    match self.eval_expression(expression, bindings, options, locals) {
+      Ok(value) => output.push_str(&value),
+      Err(Halt::Error(e)) => return Err(e),
+      Err(Halt::Empty) => {},
+      Err(Halt::Unbinded(index)) => {
+        let var = &self.vars[index];
+        match options.unbinded {
+          _ if var.nullable => {},
+          Unbinded::Error => return Err(unbinded_error(var, bindings.keys())),
+          Unbinded::Empty => {},
+          Unbinded::Verbatim => output.push_str(expression.pair_str),
+        }
+      },
+    }
+    Ok(())
+  }
+
+  /// evaluate the `let` expression and store it in its slot, a missing value
+  /// is handled the same way as in an expression placeholder, but nothing is
+  /// rendered and the local variable has no value.
+  pub(crate) fn render_let(
+    &self,
+    tag: &Let<'s>,
+    bindings: &HashMap<&str, &str>,
+    options: &RenderOptions,
+    locals: &mut Locals,
+  ) -> SigmaResult<'s, ()> {
+    let value =
+      match self.eval_expression(&tag.expression, bindings, options, locals) {
+        Ok(value) => value,
+        Err(Halt::Error(e)) => return Err(e),
+        Err(Halt::Unbinded(index)) => {
+          let var = &self.vars[index];
+          if !var.nullable && options.unbinded == Unbinded::Error {
+            return Err(unbinded_error(var, bindings.keys()));
+          }
+          locals[tag.slot] = None;
+          return Ok(());
+        },
+        Err(Halt::Empty) => {
+          locals[tag.slot] = None;
+          return Ok(());
+        },
+      };
+    let invalid = || {
+      error(
+        format!("`{}` is not a valid `{}`", preview(&value), tag.data_type),
+        tag.name_span,
+      )
+    };
+    if !self.is_valid(&tag.data_type, &value) {
+      return Err(invalid());
+    }
+    let value = Value::parse(&tag.data_type, &value).ok_or_else(invalid)?;
+    locals[tag.slot] = Some(value);
+    Ok(())
+  }
+
+  /// evaluate an expression and run the result through its pipeline.
+  fn eval_expression(
+    &self,
+    expression: &Expression<'s>,
+    bindings: &HashMap<&str, &str>,
+    options: &RenderOptions,
+    locals: &Locals,
+  ) -> Result<String, Halt> {
+    let value = self
+      .eval(&expression.expr, bindings, options, locals)?
+      .to_string();
+    if let Some((data_type, span)) = &expression.data_type {
+      if !self.is_valid(data_type, &value) {
+        return Err(Halt::Error(error(
+          format!(
+            "the expression result `{}` is not a valid `{}`",
+            value, data_type
+          ),
+          *span,
+        )));
+      }
+    }
+    if !self.fits_functions(&expression.functions, &value) {
+      return Err(Halt::Error(error(
+        format!(
+          "cannot parse the expression result `{}` into `{}`",
+          value,
+          DataType::DateTime
+        ),
+        expression.expr.span,
+      )));
+    }
+    Ok(self.run_functions(&expression.functions, value, options)?)
+  }
+
+  /// evaluate a type checked expression, only the variables that are needed
+  /// for the result must be binded, ex: the taken ternary branch.
+  pub(crate) fn eval(
+    &self,
+    expr: &Expr<'s>,
+    bindings: &HashMap<&str, &str>,
+    options: &RenderOptions,
+    locals: &Locals,
+  ) -> Result<Value, Halt> {
+    let value = match &expr.kind {
+      ExprKind::Var(index) => {
+        let var = &self.vars[*index];
+        let data = bindings.get(var.name).ok_or(Halt::Unbinded(*index))?;
+        self.validate_data_type(var, data)?;
+        Value::parse(&expr.data_type, data).ok_or_else(|| {
+          error(
+            format!("`{}` is too large for an expression", data),
+            expr.span,
+          )
+        })?
+      },
+      ExprKind::Local(slot) => locals[*slot].clone().ok_or(Halt::Empty)?,
+      ExprKind::Literal(literal) => {
+        Value::parse(&expr.data_type, literal).unwrap()
+      },
+      ExprKind::Unary(op, operand) => {
+        match (*op, self.eval(operand, bindings, options, locals)?) {
+          ("!", Value::Bool(b)) => Value::Bool(!b),
+          (_, Value::Int(n)) => {
+            Value::Int(n.checked_neg().ok_or_else(|| {
+              error("attempt to negate with overflow".to_owned(), expr.span)
+            })?)
+          },
+          (_, Value::Float(n)) => Value::Float(-n),
+          _ => unreachable!("checked at parse time"),
+        }
+      },
+      ExprKind::Ternary(cond, then, otherwise) => {
+        match self.eval(cond, bindings, options, locals)? {
+          Value::Bool(true) => self.eval(then, bindings, options, locals)?,
+          _ => self.eval(otherwise, bindings, options, locals)?,
+        }
+      },
+      ExprKind::Message(key, args) => {
+        let mut values = Vec::with_capacity(args.len());
+        for (name, arg) in args {
+          let value = self.eval(arg, bindings, options, locals)?;
+          values.push((*name, value.to_string()));
+        }
+        let args: Vec<_> = values
+          .iter()
+          .map(|(name, value)| (*name, value.as_str()))
+          .collect();
+        let message = self.translate(key, &args, options).ok_or_else(|| {
+          let locale = options.locale.as_deref().unwrap_or(DEFAULT_LOCALE);
+          error(format!("no `{}` message in `{}`", key, locale), expr.span)
+        })?;
+        Value::Str(message)
+      },
+      ExprKind::Binary(op, left, right) => {
+        let left_value = self.eval(left, bindings, options, locals)?;
+        match (*op, left_value) {
+          // short circuit
+          ("&&", Value::Bool(false)) => Value::Bool(false),
+          ("||", Value::Bool(true)) => Value::Bool(true),
+          ("&&", _) | ("||", _) => {
+            self.eval(right, bindings, options, locals)?
+          },
+          ("~", l) => Value::Str(format!(
+            "{}{}",
+            l,
+            self.eval(right, bindings, options, locals)?
+          )),
+          (op, l) => {
+            let r = self.eval(right, bindings, options, locals)?;
+            match op {
+              "==" | "!=" | "<" | "<=" | ">" | ">=" => {
+                Value::Bool(compare(op, &l, &r))
+              },
+              op => arithmetic(op, l, r)
+                .map_err(|message| error(message.to_owned(), expr.span))?,
+            }
+          },
+        }
+      },
+    };
+    if let Value::Int(n) = value {
+      let n = n.to_string();
+      if !self.is_valid(&expr.data_type, &n) {
+        return Err(
+          error(
+            format!("`{}` is out of the `{}` range", n, expr.data_type),
+            expr.span,
+          )
+          .into(),
+        );
+      }
+    }
+    Ok(value)
+  }
+}
+
+impl Value {
+  /// parse a valid value of that data type, fails only for the `u128`
+  /// values out of the `i128` range.
+  pub(crate) fn parse(data_type: &DataType, data: &str) -> Option<Self> {
+    use crate::DataType::*;
+    let value = match data_type {
+      F32 | F64 => Value::Float(data.parse().ok()?),
+      _ if data_type.is_numeric() => Value::Int(data.parse().ok()?),
+      Bool => Value::Bool(data.parse().ok()?),
+      _ => Value::Str(data.to_owned()),
+    };
+    Some(value)
+  }
+
+  fn as_f64(&self) -> f64 {
+    match self {
+      Value::Int(n) => *n as f64,
+      Value::Float(n) => *n,
+      _ => f64::NAN,
+    }
+  }
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Value::Int(n) => write!(f, "{}", n),
+      Value::Float(n) => write!(f, "{}", n),
+      Value::Bool(b) => write!(f, "{}", b),
+      Value::Str(s) => write!(f, "{}", s),
+    }
+  }
+}
+
+fn compare(op: &str, left: &Value, right: &Value) -> bool {
+  let ordering = match (left, right) {
+    (Value::Int(l), Value::Int(r)) => Some(l.cmp(r)),
+    (Value::Str(l), Value::Str(r)) => Some(l.cmp(r)),
+    (Value::Bool(l), Value::Bool(r)) => Some(l.cmp(r)),
+    (l, r) => l.as_f64().partial_cmp(&r.as_f64()),
+  };
+  match op {
+    "==" => ordering == Some(Ordering::Equal),
+    "!=" => ordering != Some(Ordering::Equal),
+    "<" => ordering == Some(Ordering::Less),
+    "<=" => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+    ">" => ordering == Some(Ordering::Greater),
+    _ => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+  }
+}
+
+fn arithmetic(op: &str, left: Value, right: Value) -> Result<Value, &str> {
+  if let (Value::Int(l), Value::Int(r)) = (&left, &right) {
+    if (op == "/" || op == "%") && *r == 0 {
+      return Err("attempt to divide by zero");
+    }
+    let result = match op {
+      "+" => l.checked_add(*r),
+      "-" => l.checked_sub(*r),
+      "*" => l.checked_mul(*r),
+      "/" => l.checked_div(*r),
+      _ => l.checked_rem(*r),
+    };
+    return result.map(Value::Int).ok_or("arithmetic overflow");
+  }
+  let (l, r) = (left.as_f64(), right.as_f64());
+  if (op == "/" || op == "%") && r == 0.0 {
+    return Err("attempt to divide by zero");
+  }
+  let result = match op {
+    "+" => l + r,
+    "-" => l - r,
+    "*" => l * r,
+    "/" => l / r,
+    _ => l % r,
+  };
+  Ok(Value::Float(result))
+}
+
+/// the data type of an arithmetic operation result, the same data type if
+/// both are the same, `f64` with a floating point number or `i128`.
+fn arithmetic_type(left: &DataType, right: &DataType) -> DataType {
+  use crate::DataType::*;
+  match (left, right) {
+    (l, r) if l == r => l.clone(),
+    (F32, _) | (F64, _) | (_, F32) | (_, F64) => F64,
+    _ => I128,
+  }
+}
+
+/// every data type other than numbers and `bool` is compared as text.
+fn is_text(data_type: &DataType) -> bool {
+  data_type.is_assignable_to(&DataType::Str)
+}
+
+/// is that expression pair only a number literal.
+fn is_number(pair: &Pair<Rule>) -> bool {
+  is_leaf(pair, Rule::number)
+}
+
+/// is that expression pair only one pair of that rule, ex: a number literal.
+fn is_leaf(pair: &Pair<Rule>, rule: Rule) -> bool {
+  let mut inner: Vec<_> = pair.clone().into_inner().collect();
+  match inner.len() {
+    0 => pair.as_rule() == rule,
+    1 => is_leaf(&inner.remove(0), rule),
+    _ => false,
+  }
+}
+
+fn join<'a>(input: &'a str, start: Span<'a>, end: Span<'a>) -> Span<'a> {
+  Span::new(input, start.start(), end.end()).unwrap()
+}
+
+fn mismatched(op: &str, expected: &str, operand: &Expr) -> PestError<Rule> {
+  error(
+    format!(
+      "mismatched types: `{}` expects {} but got `{}`",
+      op, expected, operand.data_type
+    ),
+    operand.span,
+  )
+}
+
+pub(crate) fn error(message: String, span: Span) -> PestError<Rule> {
+  PestError::new_from_span(ErrorVariant::CustomError { message }, span)
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::Sigma;
+  use std::collections::HashMap;
+
+  fn render(input: &str, bindings: &[(&str, &str)]) -> String {
+    let sigma = Sigma::new(input).parse().unwrap();
+    let bindings: HashMap<_, _> = bindings.iter().cloned().collect();
+    sigma.render(&bindings).unwrap()
+  }
+
+  fn parse_error(input: &str) -> String {
+    Sigma::new(input).parse().unwrap_err().to_string()
+  }
+
+  #[test]
+  fn arithmetic() {
+    let bindings = [("price", "2.5"), ("qty", "3")];
+    assert_eq!(render("{{ price * qty: f64 }}", &bindings), "7.5");
+    assert_eq!(render("{{ 1 + 2 * 3 - -4 }}", &[]), "11");
+    assert_eq!(render("{{ (1 + 2) * 3 / 2 % 3 }}", &[]), "1");
+    let input = "{{ n: u8 }} {{ n * 2 }} {{ n + 0.5 }}";
+    assert_eq!(render(input, &[("n", "100")]), "100 200 100.5");
+    let sigma = Sigma::new(input).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("n", "200");
+    let err = sigma.render(&bindings).unwrap_err().to_string();
+    assert!(err.contains("`400` is out of the `U8` range"));
+  }
+
+  #[test]
+  fn strings_and_logic() {
+    let input = "{{ first ~ \" \" ~ last | UPPERCASE }}";
+    let bindings = [("first", "Ada"), ("last", "Lovelace")];
+    assert_eq!(render(input, &bindings), "ADA LOVELACE");
+    let input =
+      "{{ age: u8 }} {{ age >= 18 && !banned: bool }} {{ name < \"b\" }}";
+    let bindings = [("age", "20"), ("banned", "false"), ("name", "ada")];
+    assert_eq!(render(input, &bindings), "20 true true");
+    assert_eq!(render("{{ 2 == 2.0 || x }}", &[("x", "true")]), "true");
+  }
+
+  #[test]
+  fn ternary() {
+    let input = "{{ count == 1 ? \"item\" : \"items\" }}";
+    assert_eq!(render(input, &[("count", "1")]), "item");
+    let input = "{{ count: u32 }} {{ count == 1 ? \"item\" : \"items\" }}";
+    assert_eq!(render(input, &[("count", "3")]), "3 items");
+    let input = "{{ n: u8 }}{{ n > 9 ? n : 0.5 }} {{ a ? b ? 1 : 2 : 3 }}";
+    let bindings = [("n", "10"), ("a", "true"), ("b", "false")];
+    assert_eq!(render(input, &bindings), "1010 2");
+    // only the taken branch must be binded.
+    let input = "{{ vip ? title ~ \" \" ~ name : name }}";
+    let bindings = [("vip", "false"), ("name", "ada")];
+    assert_eq!(render(input, &bindings), "ada");
+    assert!(parse_error("{{ 1 ? 2 : 3 }}").contains("`?` expects `Bool`"));
+    let err = parse_error("{{ x: u8 }}{{ x > 1 ? x : \"none\" }}");
+    assert!(err.contains("the branches are `U8` and `Str`"));
+  }
+
+  #[test]
+  fn locals() {
+    let input = "{{# let total: f64 = price * qty }}\
+                 {{ total | FORMAT_NUMBER(2) }} {{ total / 2 }}";
+    let bindings = [("price", "2.5"), ("qty", "3")];
+    assert_eq!(render(input, &bindings), "7.50 3.75");
+    let sigma = Sigma::new(input).strict().parse().unwrap();
+    let names: Vec<_> = sigma.variables().iter().map(|v| v.name).collect();
+    assert_eq!(names, vec!["price", "qty"]);
+    assert!(sigma.json_schema()["properties"].get("total").is_none());
+    let mut bindings: HashMap<_, _> = bindings.iter().cloned().collect();
+    bindings.insert("total", "1");
+    let errors = sigma.check(&bindings).unwrap_err();
+    assert!(errors[0].to_string().contains("unused binding: `total`"));
+    let input = "{{# let n: u8 = 1 }}{{# set n = n * 10 | ROUND }}{{ n }} \
+                 {{# let n = \"shadow\" }}{{ n }}";
+    assert_eq!(render(input, &[]), "10 shadow");
+    // a missing nullable value leaves the local without a value.
+    let input = "{{ bio?: str }}{{# let b = bio ~ \"!\" }}[{{ b }}]";
+    assert_eq!(render(input, &[]), "[]");
+    let err = parse_error("{{# let total = 1 }}{{# set totl = 2 }}");
+    assert!(err.contains("did you mean: `total`"));
+    let err = parse_error("{{# let n = 1 }}{{# set n = \"x\" }}");
+    assert!(err.contains("expected `I64` but the expression is `Str`"));
+  }
+
+  #[test]
+  fn type_errors() {
+    assert!(parse_error("{{ a: str }}{{ a + 1 }}").contains("use `~`"));
+    assert!(parse_error("{{ a: u8 }}{{ a == \"x\" }}").contains("compare"));
+    assert!(parse_error("{{ a * b }}").contains("cannot infer"));
+    assert!(parse_error("{{ 1 && true }}").contains("`&&` expects `Bool`"));
+    assert!(parse_error("{{ 1 + 1: str }}").contains("expected `Str`"));
+    assert!(parse_error("{{ 1 + 1 | TRIM }}").contains("function TRIM"));
+    let input =
+      "{{ x: u128 }}{{ x + 170141183460469231731687303715884105728 }}";
+    assert!(parse_error(input).contains("is too large"));
+  }
+
+  #[test]
+  fn runtime_errors() {
+    let sigma = Sigma::new("{{ a / b: i32 }}").parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("a", "1");
+    bindings.insert("b", "0");
+    let err = sigma.render(&bindings).unwrap_err().to_string();
+    assert!(err.contains("divide by zero"));
+    bindings.insert("b", "x");
+    let err = sigma.render(&bindings).unwrap_err().to_string();
+    assert!(err.contains("cannot parse input `x` into `I32`"));
+    bindings.remove("b");
+    let err = sigma.render(&bindings).unwrap_err().to_string();
+    assert!(err.contains("unbinded variable: `b`"));
+  }
+}
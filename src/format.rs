@@ -0,0 +1,277 @@
+use crate::{
+  parser::{Rule, SigmaParser},
+  Sigma, SigmaResult,
+};
+use pest::{iterators::Pair, Parser};
+
+impl<'s> Sigma<'s> {
+  /// Format the template, the placeholders are written with one space inside
+  /// the braces and around the operators, and with `|` pipes, the text
+  /// between them is kept as is.
+  ///
+  /// only the syntax is needed, the template does not have to be parsed.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// let sigma = Sigma::new("Hi {{username:str|>UPPERCASE}}, {{a+b*2}}");
+  /// assert_eq!(
+  ///   sigma.format().unwrap(),
+  ///   "Hi {{ username: str | UPPERCASE }}, {{ a + b * 2 }}"
+  /// );
+  /// ```
+  pub fn format(&self) -> SigmaResult<'s, String> {
+    let pairs = SigmaParser::parse(Rule::sigma, self.input)?;
+    let mut output = String::with_capacity(self.input.len());
+    write_nodes(&mut output, self.input, pairs, 0..self.input.len());
+    Ok(output)
+  }
+}
+
+/// write the tags of that range of the input and the text between them.
+fn write_nodes<'s>(
+  output: &mut String,
+  input: &'s str,
+  pairs: impl Iterator<Item = Pair<'s, Rule>>,
+  range: std::ops::Range<usize>,
+) {
+  let mut end = range.start;
+  for pair in pairs.filter(|pair| pair.as_rule() != Rule::EOI) {
+    let span = pair.as_span();
+    output.push_str(&input[end..span.start()]);
+    end = span.end();
+    write(output, input, pair);
+  }
+  output.push_str(&input[end..range.end]);
+}
+
+/// write a pair in its canonical form.
+fn write<'s>(output: &mut String, input: &'s str, pair: Pair<'s, Rule>) {
+  let rule = pair.as_rule();
+  let raw = pair.as_str();
+  let mut inner = pair.into_inner().peekable();
+  match rule {
+    Rule::var_pair | Rule::expr_pair => {
+      output.push_str("{{ ");
+      for pair in inner {
+        match pair.as_rule() {
+          Rule::pair_open | Rule::pair_close => {},
+          Rule::data_type_sep => output.push(':'),
+          Rule::data_type => {
+            output.push(' ');
+            output.push_str(pair.as_str());
+          },
+          _ => write(output, input, pair),
+        }
+      }
+      output.push_str(" }}");
+    },
+    Rule::var => {
+      let mut typed = false;
+      for pair in inner {
+        match pair.as_rule() {
+          Rule::data_type_sep => {
+            output.push(':');
+            typed = true;
+          },
+          Rule::data_type => {
+            // `{{ name str }}` is typed too.
+            output.push_str(if typed { " " } else { ": " });
+            output.push_str(pair.as_str());
+          },
+          _ => write(output, input, pair),
+        }
+      }
+    },
+    Rule::constraint => {
+      output.push('(');
+      write(output, input, inner.next().unwrap());
+      output.push(')');
+    },
+    Rule::length => {
+      output.push_str("len ");
+      write(output, input, inner.next().unwrap());
+    },
+    Rule::variants | Rule::arguments | Rule::macro_params | Rule::call_args => {
+      let (open, close) = match rule {
+        Rule::variants => ("", ""),
+        _ => ("(", ")"),
+      };
+      output.push_str(open);
+      write_list(output, input, inner);
+      output.push_str(close);
+    },
+    Rule::function => {
+      let _pipe = inner.next();
+      output.push_str(" | ");
+      inner.for_each(|pair| write(output, input, pair));
+    },
+    Rule::or_expr
+    | Rule::and_expr
+    | Rule::comparison
+    | Rule::concat
+    | Rule::sum
+    | Rule::product => {
+      for pair in inner {
+        match pair.as_rule() {
+          Rule::or_op
+          | Rule::and_op
+          | Rule::cmp_op
+          | Rule::concat_op
+          | Rule::sum_op
+          | Rule::product_op => {
+            output.push(' ');
+            output.push_str(pair.as_str());
+            output.push(' ');
+          },
+          _ => write(output, input, pair),
+        }
+      }
+    },
+    Rule::ternary => {
+      write(output, input, inner.next().unwrap());
+      if let (Some(then), Some(other)) = (inner.next(), inner.next()) {
+        output.push_str(" ? ");
+        write(output, input, then);
+        output.push_str(" : ");
+        write(output, input, other);
+      }
+    },
+    Rule::primary => {
+      let pair = inner.next().unwrap();
+      if pair.as_rule() == Rule::expr {
+        output.push('(');
+        write(output, input, pair);
+        output.push(')');
+      } else {
+        write(output, input, pair);
+      }
+    },
+    Rule::message => {
+      output.push_str("t(");
+      write_list(output, input, inner);
+      output.push(')');
+    },
+    Rule::message_arg | Rule::macro_param => {
+      let first = inner.next().unwrap();
+      match inner.peek().map(|pair| pair.as_rule()) {
+        // `name = expr`
+        Some(Rule::expr) => {
+          output.push_str(first.as_str());
+          output.push_str(" = ");
+          write(output, input, inner.next().unwrap());
+        },
+        // `name: type`
+        Some(Rule::data_type_sep) => {
+          output.push_str(first.as_str());
+          output.push_str(": ");
+          output.push_str(inner.nth(1).unwrap().as_str());
+        },
+        _ => write(output, input, first),
+      }
+    },
+    Rule::let_pair => {
+      output.push_str("{{# ");
+      for pair in inner {
+        match pair.as_rule() {
+          Rule::pair_open | Rule::pair_close => {},
+          Rule::let_keyword => output.push_str(pair.as_str()),
+          Rule::var_name => {
+            output.push(' ');
+            output.push_str(pair.as_str());
+          },
+          Rule::data_type_sep => output.push(':'),
+          Rule::data_type => {
+            output.push(' ');
+            output.push_str(pair.as_str());
+          },
+          Rule::expr => {
+            output.push_str(" = ");
+            write(output, input, pair);
+          },
+          _ => write(output, input, pair),
+        }
+      }
+      output.push_str(" }}");
+    },
+    Rule::macro_def => {
+      write(output, input, inner.next().unwrap());
+      let body = inner.next().unwrap();
+      let span = body.as_span();
+      write_nodes(output, input, body.into_inner(), span.start()..span.end());
+      write(output, input, inner.next().unwrap());
+    },
+    Rule::macro_open => {
+      let name = inner.nth(1).unwrap().as_str();
+      output.push_str("{{# macro ");
+      output.push_str(name);
+      write(output, input, inner.next().unwrap());
+      output.push_str(" }}");
+    },
+    Rule::macro_close => output.push_str("{{/ macro }}"),
+    Rule::call_pair => {
+      let name = inner.nth(1).unwrap().as_str();
+      output.push_str("{{> ");
+      output.push_str(name);
+      write(output, input, inner.next().unwrap());
+      output.push_str(" }}");
+    },
+    Rule::expr | Rule::unary | Rule::bounds | Rule::argument => {
+      inner.for_each(|pair| write(output, input, pair));
+    },
+    // the leaves, like the names, the strings and the numbers.
+    _ => output.push_str(raw),
+  }
+}
+
+/// write the pairs separated by `, `.
+fn write_list<'s>(
+  output: &mut String,
+  input: &'s str,
+  pairs: impl Iterator<Item = Pair<'s, Rule>>,
+) {
+  for (i, pair) in pairs.enumerate() {
+    if i > 0 {
+      output.push_str(", ");
+    }
+    write(output, input, pair);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::Sigma;
+
+  fn format(input: &str) -> String {
+    Sigma::new(input).format().unwrap()
+  }
+
+  #[test]
+  fn canonical_tags() {
+    let cases = [
+      ("{{username:str|>UPPERCASE}}", "{{ username: str | UPPERCASE }}"),
+      ("{{ name?|TRIM|>  REPLACE(\"a\" ,\"b\") }}", "{{ name? | TRIM | REPLACE(\"a\", \"b\") }}"),
+      ("{{\n  id: u8( 1..=9 )\n}}", "{{ id: u8(1..=9) }}"),
+      ("{{ tag: str(len  ..5) }}{{ c: enum(a,b ,c) }}", "{{ tag: str(len ..5) }}{{ c: enum(a, b, c) }}"),
+      ("{{a+-b*(c-1)>=2&&!ok?\"x\":y~\"!\"}}", "{{ a + -b * (c - 1) >= 2 && !ok ? \"x\" : y ~ \"!\" }}"),
+      ("{{ price*2:f64|ROUND(2) }}", "{{ price * 2: f64 | ROUND(2) }}"),
+      ("{{ t( \"welcome\",name,count=n+1 ) }}", "{{ t(\"welcome\", name, count = n + 1) }}"),
+      ("{{#let total:u32=a+b}}{{#  set total = total*2 }}", "{{# let total: u32 = a + b }}{{# set total = total * 2 }}"),
+      ("{{#macro row(name,n:u8)}}<{{name}}>{{/macro}}{{>row(\"a\",1)}}", "{{# macro row(name, n: u8) }}<{{ name }}>{{/ macro }}{{> row(\"a\", 1) }}"),
+    ];
+    for (input, expected) in cases.iter() {
+      assert_eq!(&format(input), expected, "{}", input);
+      // formatting is idempotent.
+      assert_eq!(&format(expected), expected);
+    }
+  }
+
+  #[test]
+  fn keeps_the_text() {
+    let input = "\n\n  Hi {{name}} ,\r\n\t{ not a tag } {{ }} é\n\n";
+    assert_eq!(
+      format(input),
+      "\n\n  Hi {{ name }} ,\r\n\t{ not a tag } {{ }} é\n\n"
+    );
+  }
+}
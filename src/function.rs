@@ -0,0 +1,211 @@
+use crate::{i18n::DEFAULT_LOCALE, DataType, RenderOptions};
+use chrono::{DateTime, Utc};
+use pest::Span;
+use std::{fmt, sync::Arc};
+
+type Call = dyn Fn(String, &Context) -> String + Send + Sync;
+type CheckArgs = fn(&[String]) -> Result<(), String>;
+
+/// A helper function that could be used in the template variables pipeline.
+///
+/// Example:
+/// ```
+/// # use sigma::{DataType, Function, Sigma};
+/// let repeat = Function::new("REPEAT", DataType::Str, DataType::Str, |input, cx| {
+///   input.repeat(cx.arg(0).parse().unwrap())
+/// })
+/// .arg(DataType::U8);
+/// let result = Sigma::new("{{ word: str | REPEAT(3) }}")
+///   .register(repeat)
+///   .bind("word", "ha")
+///   .parse()
+///   .unwrap()
+///   .compile();
+/// assert_eq!("hahaha", result.unwrap());
+/// ```
+#[derive(Clone)]
+pub struct Function {
+  pub(crate) name: String,
+  pub(crate) input: DataType,
+  pub(crate) output: DataType,
+  pub(crate) params: Vec<DataType>,
+  pub(crate) required: usize,
+  pub(crate) check: Option<CheckArgs>,
+  pub(crate) call: Arc<Call>,
+}
+
+impl Function {
+  /// Create a helper function that takes a value of the `input` data type and
+  /// returns a value of the `output` data type.
+  ///
+  /// The Function Name Must be in UPPERCASE
+  pub fn new<F>(
+    name: &'static str,
+    input: DataType,
+    output: DataType,
+    call: F,
+  ) -> Self
+  where
+    F: Fn(String, &Context) -> String + Send + Sync + 'static,
+  {
+    Self {
+      name: name.to_uppercase(),
+      input,
+      output,
+      params: Vec::new(),
+      required: 0,
+      check: None,
+      call: Arc::new(call),
+    }
+  }
+
+  /// add a required argument, all the required arguments must come before
+  /// the optional ones.
+  ///
+  /// numeric arguments are written as numbers, ex: `ROUND(2)` and the other
+  /// ones as quoted strings, ex: `THOUSANDS(",")`.
+  pub fn arg(mut self, data_type: DataType) -> Self {
+    assert_eq!(
+      self.required,
+      self.params.len(),
+      "required arguments must come before the optional ones"
+    );
+    self.params.push(data_type);
+    self.required += 1;
+    self
+  }
+
+  /// add an optional argument.
+  pub fn optional_arg(mut self, data_type: DataType) -> Self {
+    self.params.push(data_type);
+    self
+  }
+
+  /// validate the arguments at parse time beyond their data types, the error
+  /// message is reported on the function call.
+  pub fn check_args(mut self, check: CheckArgs) -> Self {
+    self.check = Some(check);
+    self
+  }
+
+  /// the function name.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// the data type this function expects.
+  pub fn input(&self) -> &DataType {
+    &self.input
+  }
+
+  /// the data type this function returns.
+  pub fn output(&self) -> &DataType {
+    &self.output
+  }
+}
+
+impl fmt::Debug for Function {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("Function")
+      .field("name", &self.name)
+      .field("input", &self.input)
+      .field("output", &self.output)
+      .field("params", &self.params)
+      .field("required", &self.required)
+      .finish()
+  }
+}
+
+impl PartialEq for Function {
+  fn eq(&self, other: &Self) -> bool {
+    self.name == other.name
+  }
+}
+
+impl Eq for Function {}
+
+/// A bundle of helper functions registered under a namespace, so a crate can
+/// ship a pack of helpers and the templates call them as `MODULE::NAME`.
+///
+/// Example:
+/// ```
+/// # use sigma::{DataType, Function, HelperModule, Sigma};
+/// struct Text;
+///
+/// impl HelperModule for Text {
+///   fn name(&self) -> &str {
+///     "TEXT"
+///   }
+///
+///   fn functions(&self) -> Vec<Function> {
+///     vec![Function::new("SHOUT", DataType::Str, DataType::Str, |input, _| {
+///       format!("{}!", input.to_uppercase())
+///     })]
+///   }
+/// }
+///
+/// let result = Sigma::new("{{ word | TEXT::SHOUT }}")
+///   .import(Text)
+///   .bind("word", "hey")
+///   .parse()
+///   .unwrap()
+///   .compile();
+/// assert_eq!("HEY!", result.unwrap());
+/// ```
+pub trait HelperModule {
+  /// the module name, the helpers are called as `NAME::FUNCTION`.
+  ///
+  /// The Module Name Must be in UPPERCASE
+  fn name(&self) -> &str;
+
+  /// the helper functions of this module.
+  fn functions(&self) -> Vec<Function>;
+}
+
+/// What a helper function gets along with its input when it's called.
+#[derive(Debug)]
+pub struct Context<'c> {
+  args: &'c [String],
+  options: &'c RenderOptions,
+}
+
+impl<'c> Context<'c> {
+  pub(crate) fn new(args: &'c [String], options: &'c RenderOptions) -> Self {
+    Self { args, options }
+  }
+
+  /// the arguments as written in the template, already validated against
+  /// the function params.
+  pub fn args(&self) -> &[String] {
+    self.args
+  }
+
+  /// the argument at `index`.
+  ///
+  /// ## Panics
+  /// if it's an optional argument that is not used, see [`Context::args`].
+  pub fn arg(&self, index: usize) -> &str {
+    &self.args[index]
+  }
+
+  /// the current time, or the time set by [`RenderOptions::now`].
+  pub fn now(&self) -> DateTime<Utc> {
+    self.options.now.unwrap_or_else(Utc::now)
+  }
+
+  /// the render locale, set by [`RenderOptions::locale`], `en` by default.
+  pub fn locale(&self) -> &str {
+    self.options.locale.as_deref().unwrap_or(DEFAULT_LOCALE)
+  }
+}
+
+/// A call to a helper function in a variable pipeline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionCall<'a> {
+  /// the function name.
+  pub name: &'a str,
+  /// the arguments, without the quotes for string arguments.
+  pub args: Vec<String>,
+  /// where the function name is in the template.
+  pub span: Span<'a>,
+}
@@ -0,0 +1,277 @@
+use super::locale::{self, Locale, DATE_STYLES};
+use crate::{function::Context, Function};
+use chrono::{
+  format::{Item, StrftimeItems},
+  DateTime, Datelike, FixedOffset, NaiveDate, Offset, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+
+/// the date and time helpers, they accept `datetime`, `date` and `i64` unix
+/// timestamps (in seconds), the month and weekday names are written in the
+/// render locale.
+pub(crate) fn functions() -> Vec<Function> {
+  use crate::DataType::*;
+  vec![
+    Function::new("DATE_FORMAT", DateTime, Str, date_format)
+      .arg(Str)
+      .check_args(|args| check_format(&args[0])),
+    Function::new("LOCAL_DATE", DateTime, Str, local_date)
+      .optional_arg(Str)
+      .check_args(|args| match args.first() {
+        Some(style) if !DATE_STYLES.contains(&style.as_str()) => Err(format!(
+          "unknown date style `{}`, expected one of: {}",
+          style,
+          DATE_STYLES.join(", ")
+        )),
+        _ => Ok(()),
+      }),
+    Function::new("RELATIVE_TIME", DateTime, Str, relative_time),
+    Function::new("TIMEZONE", DateTime, DateTime, timezone)
+      .arg(Str)
+      .check_args(|args| parse_timezone(&args[0]).map(|_| ())),
+    Function::new("UTC", DateTime, DateTime, |input, _| {
+      parse(&input).with_timezone(&Utc).to_rfc3339()
+    }),
+  ]
+}
+
+/// `DATE_FORMAT("%d %B %Y")`: `2019-02-24T10:30:00Z` -> `24 February 2019`
+fn date_format(input: String, cx: &Context) -> String {
+  let locale = locale::find(cx.locale());
+  format(&parse(&input), cx.arg(0), locale)
+}
+
+/// `LOCAL_DATE("long")`: `2019-02-24` -> `February 24, 2019` in `en` and
+/// `24 février 2019` in `fr`, the styles are `short`, `medium` (the default),
+/// `long` and `full`.
+fn local_date(input: String, cx: &Context) -> String {
+  let style = cx.args().first().map_or("medium", String::as_str);
+  let index = DATE_STYLES.iter().position(|s| *s == style).unwrap();
+  let locale = locale::find(cx.locale());
+  format(&parse(&input), locale.dates[index], locale)
+}
+
+/// format a date with the month and weekday names of that locale, the other
+/// specifiers are left to chrono.
+fn format(
+  datetime: &DateTime<FixedOffset>,
+  format: &str,
+  locale: &Locale,
+) -> String {
+  let month = datetime.month0() as usize;
+  let weekday = datetime.weekday().num_days_from_monday() as usize;
+  let mut localized = String::new();
+  let mut chars = format.chars();
+  while let Some(c) = chars.next() {
+    if c != '%' {
+      localized.push(c);
+      continue;
+    }
+    // a specifier, with its optional padding flag, like `%-d` or `%B`.
+    let mut spec = String::from('%');
+    for c in chars.by_ref() {
+      spec.push(c);
+      if !matches!(c, '-' | '_' | '0'..='9' | ':' | '.' | '#') {
+        break;
+      }
+    }
+    let name = match spec.chars().last() {
+      Some('B') => locale.months[month],
+      Some('b') | Some('h') => locale.short_months[month],
+      Some('A') => locale.weekdays[weekday],
+      Some('a') => locale.short_weekdays[weekday],
+      _ => {
+        localized.push_str(&spec);
+        continue;
+      },
+    };
+    // a `%` in a name would be read as a specifier.
+    localized.push_str(&name.replace('%', "%%"));
+  }
+  datetime.format(&localized).to_string()
+}
+
+/// `RELATIVE_TIME`: `3 hours ago`, `in 2 days`
+fn relative_time(input: String, cx: &Context) -> String {
+  let seconds = cx.now().signed_duration_since(parse(&input)).num_seconds();
+  let (past, seconds) = (seconds >= 0, seconds.abs() as f64);
+  let minutes = (seconds / 60.0).round();
+  let hours = (seconds / 3600.0).round();
+  let days = (seconds / 86400.0).round();
+  let amount = match () {
+    _ if seconds < 45.0 => return "just now".to_owned(),
+    _ if seconds < 90.0 => "a minute".to_owned(),
+    _ if minutes < 45.0 => format!("{} minutes", minutes),
+    _ if minutes < 90.0 => "an hour".to_owned(),
+    _ if hours < 22.0 => format!("{} hours", hours),
+    _ if hours < 36.0 => "a day".to_owned(),
+    _ if days < 26.0 => format!("{} days", days),
+    _ if days < 45.0 => "a month".to_owned(),
+    _ if days < 320.0 => format!("{} months", (days / 30.4).round()),
+    _ if days < 548.0 => "a year".to_owned(),
+    _ => format!("{} years", (days / 365.25).round()),
+  };
+  if past {
+    format!("{} ago", amount)
+  } else {
+    format!("in {}", amount)
+  }
+}
+
+/// `TIMEZONE("Africa/Cairo")` or `TIMEZONE("+02:00")`: the same instant in
+/// another time zone.
+fn timezone(input: String, cx: &Context) -> String {
+  // we are sure it's valid, it's checked at parse time.
+  let offset = parse_timezone(cx.arg(0)).unwrap();
+  let datetime = parse(&input);
+  let offset = offset(&datetime.with_timezone(&Utc));
+  datetime.with_timezone(&offset).to_rfc3339()
+}
+
+/// parse any of the accepted input formats, the input is already validated
+/// against one of them, see `Sigma::run_functions`.
+pub(crate) fn parse(input: &str) -> DateTime<FixedOffset> {
+  try_parse(input).expect("validated before the helpers pipeline")
+}
+
+/// parse a `datetime`, a `date` or an `i64` timestamp, `None` for a
+/// timestamp out of the `datetime` range.
+pub(crate) fn try_parse(input: &str) -> Option<DateTime<FixedOffset>> {
+  let utc = Utc.fix();
+  if let Ok(datetime) = DateTime::parse_from_rfc3339(input) {
+    Some(datetime)
+  } else if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+    Some(utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?))
+  } else {
+    let timestamp = input.parse().ok()?;
+    Some(DateTime::from_timestamp(timestamp, 0)?.with_timezone(&utc))
+  }
+}
+
+fn check_format(format: &str) -> Result<(), String> {
+  if StrftimeItems::new(format).any(|item| item == Item::Error) {
+    return Err(format!("invalid date format `{}`", format));
+  }
+  Ok(())
+}
+
+type Offsetter = Box<dyn Fn(&DateTime<Utc>) -> FixedOffset>;
+
+/// a fixed offset like `+02:00` or a time zone name like `Africa/Cairo`,
+/// returns the offset at some instant.
+fn parse_timezone(name: &str) -> Result<Offsetter, String> {
+  if let Ok(offset) = name.parse::<FixedOffset>() {
+    return Ok(Box::new(move |_| offset));
+  }
+  match name.parse::<Tz>() {
+    Ok(tz) => Ok(Box::new(move |utc| {
+      tz.offset_from_utc_datetime(&utc.naive_utc()).fix()
+    })),
+    Err(_) => Err(format!("unknown time zone `{}`", name)),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::RenderOptions;
+
+  fn call(name: &str, input: &str, args: &[&str]) -> String {
+    call_in("en", name, input, args)
+  }
+
+  fn call_in(locale: &str, name: &str, input: &str, args: &[&str]) -> String {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let now = DateTime::parse_from_rfc3339("2019-02-24T12:00:00Z").unwrap();
+    let options = RenderOptions::new()
+      .now(now.with_timezone(&Utc))
+      .locale(locale);
+    let f = functions().into_iter().find(|f| f.name == name).unwrap();
+    (f.call)(input.to_owned(), &Context::new(&args, &options))
+  }
+
+  #[test]
+  fn format_dates() {
+    let format = ["%d %B %Y %H:%M"];
+    assert_eq!(
+      call("DATE_FORMAT", "2019-02-24T10:30:00+02:00", &format),
+      "24 February 2019 10:30"
+    );
+    assert_eq!(
+      call("DATE_FORMAT", "2019-02-24", &format),
+      "24 February 2019 00:00"
+    );
+    assert_eq!(call("DATE_FORMAT", "0", &format), "01 January 1970 00:00");
+    assert!(check_format("%Y-%m-%d").is_ok());
+    assert!(check_format("%Q").is_err());
+  }
+
+  #[test]
+  fn locales() {
+    let input = "2019-02-24T10:30:00Z";
+    let format = ["%A %-d %B (%a %b) %H:%M %%B"];
+    assert_eq!(
+      call_in("fr-FR", "DATE_FORMAT", input, &format),
+      "dimanche 24 février (dim. févr.) 10:30 %B"
+    );
+    assert_eq!(
+      call_in("xx", "DATE_FORMAT", input, &format),
+      "Sunday 24 February (Sun Feb) 10:30 %B"
+    );
+    assert_eq!(call("LOCAL_DATE", input, &[]), "Feb 24, 2019");
+    assert_eq!(call("LOCAL_DATE", input, &["short"]), "2/24/19");
+    assert_eq!(call_in("en-GB", "LOCAL_DATE", input, &[]), "24 Feb 2019");
+    assert_eq!(
+      call_in("de", "LOCAL_DATE", input, &["full"]),
+      "Sonntag, 24. Februar 2019"
+    );
+    assert_eq!(
+      call_in("ru", "LOCAL_DATE", input, &["long"]),
+      "24 февраля 2019 г."
+    );
+    assert_eq!(
+      call_in("ja", "LOCAL_DATE", input, &["full"]),
+      "2019年2月24日日曜日"
+    );
+  }
+
+  #[test]
+  fn relative() {
+    assert_eq!(
+      call("RELATIVE_TIME", "2019-02-24T11:59:30Z", &[]),
+      "just now"
+    );
+    assert_eq!(
+      call("RELATIVE_TIME", "2019-02-24T09:00:00Z", &[]),
+      "3 hours ago"
+    );
+    assert_eq!(
+      call("RELATIVE_TIME", "2019-02-26T12:00:00Z", &[]),
+      "in 2 days"
+    );
+    assert_eq!(call("RELATIVE_TIME", "2019-01-20", &[]), "a month ago");
+    assert_eq!(call("RELATIVE_TIME", "2015-02-24", &[]), "4 years ago");
+  }
+
+  #[test]
+  fn timezones() {
+    let input = "2019-07-01T10:00:00Z";
+    assert_eq!(
+      call("TIMEZONE", input, &["Europe/Berlin"]),
+      "2019-07-01T12:00:00+02:00"
+    );
+    assert_eq!(
+      call("TIMEZONE", "2019-01-01T10:00:00Z", &["Europe/Berlin"]),
+      "2019-01-01T11:00:00+01:00"
+    );
+    assert_eq!(
+      call("TIMEZONE", input, &["-05:00"]),
+      "2019-07-01T05:00:00-05:00"
+    );
+    assert_eq!(
+      call("UTC", "2019-07-01T12:00:00+02:00", &[]),
+      "2019-07-01T10:00:00+00:00"
+    );
+    assert!(parse_timezone("Mars/Olympus").is_err());
+  }
+}
@@ -0,0 +1,448 @@
+//! The bundled formatting data of some common locales.
+
+/// how a locale writes the numbers and the dates.
+pub(crate) struct Locale {
+  pub(crate) tag: &'static str,
+  pub(crate) decimal: &'static str,
+  pub(crate) group: &'static str,
+  /// `¤` is the currency symbol and `#` the amount.
+  pub(crate) currency: &'static str,
+  /// `#` is the amount.
+  pub(crate) percent: &'static str,
+  pub(crate) months: [&'static str; 12],
+  pub(crate) short_months: [&'static str; 12],
+  /// from Monday to Sunday.
+  pub(crate) weekdays: [&'static str; 7],
+  pub(crate) short_weekdays: [&'static str; 7],
+  /// the `short`, `medium`, `long` and `full` date formats.
+  pub(crate) dates: [&'static str; 4],
+}
+
+/// the date styles of `Locale::dates`.
+pub(crate) const DATE_STYLES: [&str; 4] = ["short", "medium", "long", "full"];
+
+/// the data of that locale, or of its language, `fr-CA` then `fr`, or the
+/// English one.
+pub(crate) fn find(locale: &str) -> &'static Locale {
+  let language = locale.split(['-', '_']).next().unwrap_or(locale);
+  let same = |a: &str, b: &str| a.replace('_', "-").eq_ignore_ascii_case(b);
+  LOCALES
+    .iter()
+    .find(|data| same(locale, data.tag))
+    .or_else(|| LOCALES.iter().find(|data| same(language, data.tag)))
+    .unwrap_or(&LOCALES[0])
+}
+
+static LOCALES: [Locale; 11] = [
+  Locale {
+    tag: "en",
+    decimal: ".",
+    group: ",",
+    currency: "¤#",
+    percent: "#%",
+    months: [
+      "January",
+      "February",
+      "March",
+      "April",
+      "May",
+      "June",
+      "July",
+      "August",
+      "September",
+      "October",
+      "November",
+      "December",
+    ],
+    short_months: [
+      "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct",
+      "Nov", "Dec",
+    ],
+    weekdays: [
+      "Monday",
+      "Tuesday",
+      "Wednesday",
+      "Thursday",
+      "Friday",
+      "Saturday",
+      "Sunday",
+    ],
+    short_weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    dates: ["%-m/%-d/%y", "%b %-d, %Y", "%B %-d, %Y", "%A, %B %-d, %Y"],
+  },
+  Locale {
+    tag: "en-GB",
+    decimal: ".",
+    group: ",",
+    currency: "¤#",
+    percent: "#%",
+    months: [
+      "January",
+      "February",
+      "March",
+      "April",
+      "May",
+      "June",
+      "July",
+      "August",
+      "September",
+      "October",
+      "November",
+      "December",
+    ],
+    short_months: [
+      "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sept", "Oct",
+      "Nov", "Dec",
+    ],
+    weekdays: [
+      "Monday",
+      "Tuesday",
+      "Wednesday",
+      "Thursday",
+      "Friday",
+      "Saturday",
+      "Sunday",
+    ],
+    short_weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    dates: ["%d/%m/%Y", "%-d %b %Y", "%-d %B %Y", "%A, %-d %B %Y"],
+  },
+  Locale {
+    tag: "fr",
+    decimal: ",",
+    group: "\u{202f}",
+    currency: "#\u{a0}¤",
+    percent: "#\u{a0}%",
+    months: [
+      "janvier",
+      "février",
+      "mars",
+      "avril",
+      "mai",
+      "juin",
+      "juillet",
+      "août",
+      "septembre",
+      "octobre",
+      "novembre",
+      "décembre",
+    ],
+    short_months: [
+      "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août",
+      "sept.", "oct.", "nov.", "déc.",
+    ],
+    weekdays: [
+      "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi", "dimanche",
+    ],
+    short_weekdays: ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+    dates: ["%d/%m/%Y", "%-d %b %Y", "%-d %B %Y", "%A %-d %B %Y"],
+  },
+  Locale {
+    tag: "de",
+    decimal: ",",
+    group: ".",
+    currency: "#\u{a0}¤",
+    percent: "#\u{a0}%",
+    months: [
+      "Januar",
+      "Februar",
+      "März",
+      "April",
+      "Mai",
+      "Juni",
+      "Juli",
+      "August",
+      "September",
+      "Oktober",
+      "November",
+      "Dezember",
+    ],
+    short_months: [
+      "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.",
+      "Okt.", "Nov.", "Dez.",
+    ],
+    weekdays: [
+      "Montag",
+      "Dienstag",
+      "Mittwoch",
+      "Donnerstag",
+      "Freitag",
+      "Samstag",
+      "Sonntag",
+    ],
+    short_weekdays: ["Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa.", "So."],
+    dates: ["%d.%m.%y", "%d.%m.%Y", "%-d. %B %Y", "%A, %-d. %B %Y"],
+  },
+  Locale {
+    tag: "es",
+    decimal: ",",
+    group: ".",
+    currency: "#\u{a0}¤",
+    percent: "#\u{a0}%",
+    months: [
+      "enero",
+      "febrero",
+      "marzo",
+      "abril",
+      "mayo",
+      "junio",
+      "julio",
+      "agosto",
+      "septiembre",
+      "octubre",
+      "noviembre",
+      "diciembre",
+    ],
+    short_months: [
+      "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct",
+      "nov", "dic",
+    ],
+    weekdays: [
+      "lunes",
+      "martes",
+      "miércoles",
+      "jueves",
+      "viernes",
+      "sábado",
+      "domingo",
+    ],
+    short_weekdays: ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+    dates: [
+      "%-d/%-m/%y",
+      "%-d %b %Y",
+      "%-d de %B de %Y",
+      "%A, %-d de %B de %Y",
+    ],
+  },
+  Locale {
+    tag: "it",
+    decimal: ",",
+    group: ".",
+    currency: "#\u{a0}¤",
+    percent: "#%",
+    months: [
+      "gennaio",
+      "febbraio",
+      "marzo",
+      "aprile",
+      "maggio",
+      "giugno",
+      "luglio",
+      "agosto",
+      "settembre",
+      "ottobre",
+      "novembre",
+      "dicembre",
+    ],
+    short_months: [
+      "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott",
+      "nov", "dic",
+    ],
+    weekdays: [
+      "lunedì",
+      "martedì",
+      "mercoledì",
+      "giovedì",
+      "venerdì",
+      "sabato",
+      "domenica",
+    ],
+    short_weekdays: ["lun", "mar", "mer", "gio", "ven", "sab", "dom"],
+    dates: ["%d/%m/%y", "%-d %b %Y", "%-d %B %Y", "%A %-d %B %Y"],
+  },
+  Locale {
+    tag: "pt",
+    decimal: ",",
+    group: ".",
+    currency: "¤\u{a0}#",
+    percent: "#%",
+    months: [
+      "janeiro",
+      "fevereiro",
+      "março",
+      "abril",
+      "maio",
+      "junho",
+      "julho",
+      "agosto",
+      "setembro",
+      "outubro",
+      "novembro",
+      "dezembro",
+    ],
+    short_months: [
+      "jan.", "fev.", "mar.", "abr.", "mai.", "jun.", "jul.", "ago.", "set.",
+      "out.", "nov.", "dez.",
+    ],
+    weekdays: [
+      "segunda-feira",
+      "terça-feira",
+      "quarta-feira",
+      "quinta-feira",
+      "sexta-feira",
+      "sábado",
+      "domingo",
+    ],
+    short_weekdays: ["seg.", "ter.", "qua.", "qui.", "sex.", "sáb.", "dom."],
+    dates: [
+      "%d/%m/%Y",
+      "%-d de %b de %Y",
+      "%-d de %B de %Y",
+      "%A, %-d de %B de %Y",
+    ],
+  },
+  Locale {
+    tag: "nl",
+    decimal: ",",
+    group: ".",
+    currency: "¤\u{a0}#",
+    percent: "#%",
+    months: [
+      "januari",
+      "februari",
+      "maart",
+      "april",
+      "mei",
+      "juni",
+      "juli",
+      "augustus",
+      "september",
+      "oktober",
+      "november",
+      "december",
+    ],
+    short_months: [
+      "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt",
+      "nov", "dec",
+    ],
+    weekdays: [
+      "maandag",
+      "dinsdag",
+      "woensdag",
+      "donderdag",
+      "vrijdag",
+      "zaterdag",
+      "zondag",
+    ],
+    short_weekdays: ["ma", "di", "wo", "do", "vr", "za", "zo"],
+    dates: ["%d-%m-%Y", "%-d %b %Y", "%-d %B %Y", "%A %-d %B %Y"],
+  },
+  Locale {
+    tag: "ru",
+    decimal: ",",
+    group: "\u{a0}",
+    currency: "#\u{a0}¤",
+    percent: "#\u{a0}%",
+    // the genitive forms, `24 февраля`.
+    months: [
+      "января",
+      "февраля",
+      "марта",
+      "апреля",
+      "мая",
+      "июня",
+      "июля",
+      "августа",
+      "сентября",
+      "октября",
+      "ноября",
+      "декабря",
+    ],
+    short_months: [
+      "янв.",
+      "февр.",
+      "мар.",
+      "апр.",
+      "мая",
+      "июн.",
+      "июл.",
+      "авг.",
+      "сент.",
+      "окт.",
+      "нояб.",
+      "дек.",
+    ],
+    weekdays: [
+      "понедельник",
+      "вторник",
+      "среда",
+      "четверг",
+      "пятница",
+      "суббота",
+      "воскресенье",
+    ],
+    short_weekdays: ["пн", "вт", "ср", "чт", "пт", "сб", "вс"],
+    dates: [
+      "%d.%m.%Y",
+      "%-d %b %Y г.",
+      "%-d %B %Y г.",
+      "%A, %-d %B %Y г.",
+    ],
+  },
+  Locale {
+    tag: "ja",
+    decimal: ".",
+    group: ",",
+    currency: "¤#",
+    percent: "#%",
+    months: [
+      "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月",
+      "11月", "12月",
+    ],
+    short_months: [
+      "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月",
+      "11月", "12月",
+    ],
+    weekdays: [
+      "月曜日",
+      "火曜日",
+      "水曜日",
+      "木曜日",
+      "金曜日",
+      "土曜日",
+      "日曜日",
+    ],
+    short_weekdays: ["月", "火", "水", "木", "金", "土", "日"],
+    dates: ["%Y/%m/%d", "%Y/%m/%d", "%Y年%-m月%-d日", "%Y年%-m月%-d日%A"],
+  },
+  Locale {
+    tag: "zh",
+    decimal: ".",
+    group: ",",
+    currency: "¤#",
+    percent: "#%",
+    months: [
+      "一月",
+      "二月",
+      "三月",
+      "四月",
+      "五月",
+      "六月",
+      "七月",
+      "八月",
+      "九月",
+      "十月",
+      "十一月",
+      "十二月",
+    ],
+    short_months: [
+      "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月",
+      "11月", "12月",
+    ],
+    weekdays: [
+      "星期一",
+      "星期二",
+      "星期三",
+      "星期四",
+      "星期五",
+      "星期六",
+      "星期日",
+    ],
+    short_weekdays: ["周一", "周二", "周三", "周四", "周五", "周六", "周日"],
+    dates: [
+      "%Y/%-m/%-d",
+      "%Y年%-m月%-d日",
+      "%Y年%-m月%-d日",
+      "%Y年%-m月%-d日%A",
+    ],
+  },
+];
@@ -0,0 +1,42 @@
+//! The built-in helper functions.
+use crate::{Function, HelperModule};
+
+pub(crate) mod date;
+pub(crate) mod locale;
+pub(crate) mod number;
+pub(crate) mod string;
+
+/// a group of the built-in helpers, registered under its own namespace.
+pub(crate) struct Builtin {
+  name: &'static str,
+  functions: fn() -> Vec<Function>,
+}
+
+impl HelperModule for Builtin {
+  fn name(&self) -> &str {
+    self.name
+  }
+
+  fn functions(&self) -> Vec<Function> {
+    (self.functions)()
+  }
+}
+
+/// the built-in helpers grouped as modules, `STR::TRIM`, `NUM::ROUND` and
+/// `DATE::UTC`.
+pub(crate) fn modules() -> Vec<Builtin> {
+  vec![
+    Builtin {
+      name: "STR",
+      functions: string::functions,
+    },
+    Builtin {
+      name: "NUM",
+      functions: number::functions,
+    },
+    Builtin {
+      name: "DATE",
+      functions: date::functions,
+    },
+  ]
+}
@@ -0,0 +1,257 @@
+use super::locale::{self, Locale};
+use crate::{function::Context, Function};
+
+/// the number helpers, they accept any numeric data type, the ones that
+/// return a `str` are written in the render locale.
+pub(crate) fn functions() -> Vec<Function> {
+  use crate::DataType::*;
+  vec![
+    Function::new("FORMAT_NUMBER", F64, F64, format_number).arg(U8),
+    Function::new("ROUND", F64, F64, round).optional_arg(U8),
+    Function::new("THOUSANDS", F64, Str, thousands).optional_arg(Str),
+    Function::new("NUMBER", F64, Str, number).optional_arg(U8),
+    Function::new("PERCENT", F64, Str, percent).optional_arg(U8),
+    Function::new("CURRENCY", F64, Str, currency).arg(Str),
+    Function::new("FILESIZE", F64, Str, filesize),
+  ]
+}
+
+/// `FORMAT_NUMBER(2)`: `3.14159` -> `3.14`, `3` -> `3.00`
+fn format_number(input: String, cx: &Context) -> String {
+  fixed(&input, cx.arg(0).parse().unwrap())
+}
+
+/// `ROUND`: `2.5` -> `3`, `ROUND(2)`: `3.14159` -> `3.14`
+fn round(input: String, cx: &Context) -> String {
+  if is_integer(&input) {
+    return input;
+  }
+  let decimals = cx.args().first().map_or(0, |d| d.parse().unwrap());
+  let factor = 10f64.powi(decimals);
+  let value: f64 = input.parse().unwrap();
+  ((value * factor).round() / factor).to_string()
+}
+
+/// `THOUSANDS`: `1234567.5` -> `1,234,567.5`, or with the separators of the
+/// render locale, `THOUSANDS(" ")` for another separator.
+fn thousands(input: String, cx: &Context) -> String {
+  match cx.args().first() {
+    Some(separator) => group(&normalize(&input), separator, "."),
+    None => localize(&normalize(&input), locale::find(cx.locale())),
+  }
+}
+
+/// `NUMBER`: `1234567.5` -> `1,234,567.5` in `en` and `1.234.567,5` in `de`,
+/// `NUMBER(2)` with exactly 2 decimals.
+fn number(input: String, cx: &Context) -> String {
+  let number = match cx.args().first() {
+    Some(decimals) => fixed(&input, decimals.parse().unwrap()),
+    None => normalize(&input),
+  };
+  localize(&number, locale::find(cx.locale()))
+}
+
+/// `PERCENT`: `0.125` -> `12.5%`, `PERCENT(2)`: `0.125` -> `12.50%`
+fn percent(input: String, cx: &Context) -> String {
+  let value = input.parse::<f64>().unwrap() * 100.0;
+  let value = match cx.args().first() {
+    Some(decimals) => format!("{:.*}", decimals.parse().unwrap(), value),
+    None => ((value * 100.0).round() / 100.0).to_string(),
+  };
+  let locale = locale::find(cx.locale());
+  locale.percent.replace('#', &localize(&value, locale))
+}
+
+/// `CURRENCY("USD")`: `-1234.5` -> `-$1,234.50`
+fn currency(input: String, cx: &Context) -> String {
+  let code = cx.arg(0).to_uppercase();
+  let decimals = match code.as_str() {
+    "JPY" | "KRW" | "VND" | "CLP" | "ISK" => 0,
+    "BHD" | "KWD" | "OMR" | "JOD" | "TND" => 3,
+    _ => 2,
+  };
+  let locale = locale::find(cx.locale());
+  let amount = localize(&fixed(&input, decimals), locale);
+  let (sign, amount) = match amount.strip_prefix('-') {
+    Some(amount) => ("-", amount),
+    None => ("", amount.as_str()),
+  };
+  let pattern = match symbol(&code) {
+    Some(symbol) => locale.currency.replace('¤', symbol),
+    // a code is not glued to the amount, `CHF 12`.
+    None => locale.currency.replace("¤#", "¤ #").replace('¤', &code),
+  };
+  format!("{}{}", sign, pattern.replace('#', amount))
+}
+
+fn symbol(code: &str) -> Option<&'static str> {
+  let symbol = match code {
+    "USD" => "$",
+    "EUR" => "€",
+    "GBP" => "£",
+    "JPY" => "¥",
+    "CNY" => "CN¥",
+    "INR" => "₹",
+    "KRW" => "₩",
+    "RUB" => "₽",
+    "TRY" => "₺",
+    "BRL" => "R$",
+    "CAD" => "CA$",
+    "AUD" => "A$",
+    "EGP" => "E£",
+    _ => return None,
+  };
+  Some(symbol)
+}
+
+/// `FILESIZE`: `1536` -> `1.5 KB`
+fn filesize(input: String, cx: &Context) -> String {
+  const UNITS: [&str; 6] = ["KB", "MB", "GB", "TB", "PB", "EB"];
+  let mut size = input.parse::<f64>().unwrap();
+  if size.abs() < 1024.0 {
+    return format!("{} B", size);
+  }
+  let mut unit = "B";
+  for u in &UNITS {
+    if size.abs() < 1024.0 {
+      break;
+    }
+    size /= 1024.0;
+    unit = u;
+  }
+  let size = ((size * 10.0).round() / 10.0).to_string();
+  let decimal = locale::find(cx.locale()).decimal;
+  format!("{} {}", size.replace('.', decimal), unit)
+}
+
+fn is_integer(input: &str) -> bool {
+  let digits = input.strip_prefix('-').unwrap_or(input);
+  !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+}
+
+/// write the number without an exponent, keeping big integers as they are.
+fn normalize(input: &str) -> String {
+  if is_integer(input) || !input.contains(['e', 'E']) {
+    input.to_owned()
+  } else {
+    input.parse::<f64>().unwrap().to_string()
+  }
+}
+
+/// the number with exactly `decimals` digits after the decimal point.
+fn fixed(input: &str, decimals: usize) -> String {
+  if is_integer(input) {
+    if decimals == 0 {
+      input.to_owned()
+    } else {
+      format!("{}.{}", input, "0".repeat(decimals))
+    }
+  } else {
+    format!("{:.*}", decimals, input.parse::<f64>().unwrap())
+  }
+}
+
+/// group the digits and write the decimal point as that locale does.
+fn localize(number: &str, locale: &Locale) -> String {
+  group(number, locale.group, locale.decimal)
+}
+
+/// group the integer part digits by three, and replace the decimal point.
+fn group(number: &str, separator: &str, decimal: &str) -> String {
+  let (sign, number) = match number.strip_prefix('-') {
+    Some(number) => ("-", number),
+    None => ("", number),
+  };
+  let (integer, fraction) = match number.find('.') {
+    Some(i) => number.split_at(i),
+    None => (number, ""),
+  };
+  let mut grouped = String::new();
+  for (i, digit) in integer.chars().enumerate() {
+    if i > 0 && (integer.len() - i) % 3 == 0 {
+      grouped.push_str(separator);
+    }
+    grouped.push(digit);
+  }
+  match fraction.strip_prefix('.') {
+    Some(fraction) => format!("{}{}{}{}", sign, grouped, decimal, fraction),
+    None => format!("{}{}", sign, grouped),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::RenderOptions;
+
+  fn call(name: &str, input: &str, args: &[&str]) -> String {
+    call_in("en", name, input, args)
+  }
+
+  fn call_in(locale: &str, name: &str, input: &str, args: &[&str]) -> String {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let options = RenderOptions::new().locale(locale);
+    let f = functions().into_iter().find(|f| f.name == name).unwrap();
+    (f.call)(input.to_owned(), &Context::new(&args, &options))
+  }
+
+  #[test]
+  fn format_and_round() {
+    assert_eq!(call("FORMAT_NUMBER", "3.14159", &["2"]), "3.14");
+    assert_eq!(call("FORMAT_NUMBER", "3", &["2"]), "3.00");
+    assert_eq!(call("FORMAT_NUMBER", "2.5", &["0"]), "2");
+    assert_eq!(call("ROUND", "2.5", &[]), "3");
+    assert_eq!(call("ROUND", "3.14159", &["3"]), "3.142");
+    assert_eq!(call("ROUND", "-7", &["3"]), "-7");
+  }
+
+  #[test]
+  fn thousands_separator() {
+    assert_eq!(call("THOUSANDS", "1234567.891", &[]), "1,234,567.891");
+    assert_eq!(call("THOUSANDS", "-123456", &["."]), "-123.456");
+    assert_eq!(call("THOUSANDS", "123", &[]), "123");
+    assert_eq!(
+      call(
+        "THOUSANDS",
+        "340282366920938463463374607431768211455",
+        &[" "]
+      ),
+      "340 282 366 920 938 463 463 374 607 431 768 211 455"
+    );
+    assert_eq!(call("THOUSANDS", "1.5e6", &[]), "1,500,000");
+  }
+
+  #[test]
+  fn percent_currency_filesize() {
+    assert_eq!(call("PERCENT", "0.125", &[]), "12.5%");
+    assert_eq!(call("PERCENT", "0.125", &["2"]), "12.50%");
+    assert_eq!(call("CURRENCY", "1234.5", &["USD"]), "$1,234.50");
+    assert_eq!(call("CURRENCY", "-1234.5", &["eur"]), "-€1,234.50");
+    assert_eq!(call("CURRENCY", "1234.5", &["JPY"]), "¥1,234");
+    assert_eq!(call("CURRENCY", "1234.5", &["CHF"]), "CHF 1,234.50");
+    assert_eq!(call("FILESIZE", "512", &[]), "512 B");
+    assert_eq!(call("FILESIZE", "1536", &[]), "1.5 KB");
+    assert_eq!(call("FILESIZE", "1073741824", &[]), "1 GB");
+  }
+
+  #[test]
+  fn locales() {
+    let input = "-1234567.891";
+    assert_eq!(call_in("de-AT", "NUMBER", input, &[]), "-1.234.567,891");
+    assert_eq!(
+      call_in("fr", "NUMBER", input, &["1"]),
+      "-1\u{202f}234\u{202f}567,9"
+    );
+    assert_eq!(call_in("xx", "NUMBER", input, &["0"]), "-1,234,568");
+    assert_eq!(call_in("ru", "THOUSANDS", "12345.5", &[]), "12\u{a0}345,5");
+    assert_eq!(call_in("de", "THOUSANDS", "12345.5", &[" "]), "12 345.5");
+    assert_eq!(call_in("fr", "PERCENT", "0.125", &[]), "12,5\u{a0}%");
+    assert_eq!(
+      call_in("de", "CURRENCY", "-1234.5", &["EUR"]),
+      "-1.234,50\u{a0}€"
+    );
+    assert_eq!(call_in("pt-BR", "CURRENCY", "2", &["BRL"]), "R$\u{a0}2,00");
+    assert_eq!(call_in("de", "CURRENCY", "2", &["CHF"]), "2,00\u{a0}CHF");
+    assert_eq!(call_in("nl", "FILESIZE", "1536", &[]), "1,5 KB");
+  }
+}
@@ -0,0 +1,319 @@
+use crate::{function::Context, Function};
+use regex::Regex;
+use std::sync::OnceLock;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// the string helpers, lengths are counted in user-perceived characters
+/// (grapheme clusters), so `é` written as `e` + `◌́` is one character.
+pub(crate) fn functions() -> Vec<Function> {
+  use crate::DataType::*;
+  vec![
+    Function::new("UPPERCASE", Str, Str, |input, _| input.to_uppercase()),
+    Function::new("LOWERCASE", Str, Str, |input, _| input.to_lowercase()),
+    // kept for the templates that used the old misspelled name.
+    Function::new("LOWERRCASE", Str, Str, |input, _| input.to_lowercase()),
+    Function::new("TRIM", Str, Str, |input, _| input.trim().to_owned()),
+    Function::new("TRIM_START", Str, Str, |input, _| {
+      input.trim_start().to_owned()
+    }),
+    Function::new("TRIM_END", Str, Str, |input, _| input.trim_end().to_owned()),
+    Function::new("CAPITALIZE", Str, Str, |input, _| capitalize(&input)),
+    Function::new("TITLE_CASE", Str, Str, title_case),
+    Function::new("SNAKE_CASE", Str, Str, |input, _| {
+      words(&input).join("_").to_lowercase()
+    }),
+    Function::new("KEBAB_CASE", Str, Str, |input, _| {
+      words(&input).join("-").to_lowercase()
+    }),
+    Function::new("CAMEL_CASE", Str, Str, camel_case),
+    Function::new("TRUNCATE", Str, Str, truncate)
+      .arg(Usize)
+      .optional_arg(Str),
+    Function::new("WORDWRAP", Str, Str, wordwrap)
+      .arg(Usize)
+      .check_args(|args| match args[0].parse::<usize>() {
+        Ok(0) => Err("cannot wrap to zero width".to_owned()),
+        _ => Ok(()),
+      }),
+    Function::new("INDENT", Str, Str, indent)
+      .arg(Usize)
+      .check_args(|args| match args[0].parse::<usize>() {
+        Ok(width) if width <= MAX_INDENT => Ok(()),
+        _ => Err(format!("cannot indent by more than {} spaces", MAX_INDENT)),
+      }),
+    Function::new("REPLACE", Str, Str, |input, cx| {
+      input.replace(cx.arg(0), cx.arg(1))
+    })
+    .arg(Str)
+    .arg(Str)
+    .check_args(|args| match args[0].as_str() {
+      "" => Err("cannot replace an empty string".to_owned()),
+      _ => Ok(()),
+    }),
+    Function::new("SLUG", Str, Str, |input, _| slug(&input)),
+    Function::new("REVERSE", Str, Str, |input, _| {
+      input.graphemes(true).rev().collect()
+    }),
+    Function::new("LENGTH", Str, Usize, |input, _| {
+      input.graphemes(true).count().to_string()
+    }),
+    Function::new("STRIP_HTML", Str, Str, strip_html),
+    Function::new("URL_ENCODE", Str, Str, |input, _| url_encode(&input)),
+    Function::new("BASE64", Str, Str, |input, _| base64(input.as_bytes())),
+  ]
+}
+
+/// `hELLO wORLD` -> `Hello world`
+fn capitalize(input: &str) -> String {
+  let mut chars = input.chars();
+  match chars.next() {
+    Some(first) => first
+      .to_uppercase()
+      .chain(chars.flat_map(char::to_lowercase))
+      .collect(),
+    None => String::new(),
+  }
+}
+
+/// `hELLO wORLD` -> `Hello World`
+fn title_case(input: String, _: &Context) -> String {
+  input.split_word_bounds().map(capitalize).collect()
+}
+
+/// `hello_world-foo` -> `helloWorldFoo`
+fn camel_case(input: String, _: &Context) -> String {
+  words(&input)
+    .iter()
+    .enumerate()
+    .map(|(i, word)| match i {
+      0 => word.to_lowercase(),
+      _ => capitalize(word),
+    })
+    .collect()
+}
+
+/// split the input into words on any non alphanumeric char and on the case
+/// changes, `XMLHttpRequest v2` -> `XML`, `Http`, `Request`, `v2`.
+fn words(input: &str) -> Vec<String> {
+  let mut words = Vec::new();
+  for part in input.split(|c: char| !c.is_alphanumeric()) {
+    let chars: Vec<char> = part.chars().collect();
+    let mut word = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+      let boundary = i > 0
+        && c.is_uppercase()
+        && (!chars[i - 1].is_uppercase()
+          || chars.get(i + 1).is_some_and(|next| next.is_lowercase()));
+      if boundary && !word.is_empty() {
+        words.push(std::mem::take(&mut word));
+      }
+      word.push(c);
+    }
+    if !word.is_empty() {
+      words.push(word);
+    }
+  }
+  words
+}
+
+/// `TRUNCATE(5)`: `Hello World` -> `Hello…`, `TRUNCATE(5, "...")` for
+/// another ellipsis.
+fn truncate(input: String, cx: &Context) -> String {
+  let length: usize = cx.arg(0).parse().unwrap();
+  let ellipsis = cx.args().get(1).map_or("…", String::as_str);
+  match input.grapheme_indices(true).nth(length) {
+    Some((end, _)) => format!("{}{}", input[..end].trim_end(), ellipsis),
+    None => input,
+  }
+}
+
+/// `WORDWRAP(10)`: break the lines at whitespace so they are not longer than
+/// 10 chars when possible, long words are not broken.
+fn wordwrap(input: String, cx: &Context) -> String {
+  let width: usize = cx.arg(0).parse().unwrap();
+  let mut lines = Vec::new();
+  for line in input.lines() {
+    let mut current = String::new();
+    let mut current_width = 0;
+    for word in line.split_whitespace() {
+      let word_width = word.graphemes(true).count();
+      if current_width > 0 && current_width + 1 + word_width > width {
+        lines.push(std::mem::take(&mut current));
+        current_width = 0;
+      }
+      if current_width > 0 {
+        current.push(' ');
+        current_width += 1;
+      }
+      current.push_str(word);
+      current_width += word_width;
+    }
+    lines.push(current);
+  }
+  lines.join("\n")
+}
+
+/// the widest `INDENT`, so a typo does not allocate gigabytes.
+const MAX_INDENT: usize = 1024;
+
+/// `INDENT(2)`: add 2 spaces before every non-empty line.
+fn indent(input: String, cx: &Context) -> String {
+  let spaces = " ".repeat(cx.arg(0).parse().unwrap());
+  input
+    .split('\n')
+    .map(|line| {
+      if line.trim().is_empty() {
+        line.to_owned()
+      } else {
+        format!("{}{}", spaces, line)
+      }
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// `Crème Brûlée!` -> `creme-brulee`
+fn slug(input: &str) -> String {
+  let mut slug = String::new();
+  for c in input.nfkd().filter(|c| !is_combining_mark(*c)) {
+    if c.is_alphanumeric() {
+      slug.extend(c.to_lowercase());
+    } else if !slug.is_empty() && !slug.ends_with('-') {
+      slug.push('-');
+    }
+  }
+  slug.trim_end_matches('-').to_owned()
+}
+
+/// `<p>Hello <b>World</b></p>` -> `Hello World`
+fn strip_html(input: String, _: &Context) -> String {
+  static COMMENTS: OnceLock<Regex> = OnceLock::new();
+  static TAGS: OnceLock<Regex> = OnceLock::new();
+  let comments =
+    COMMENTS.get_or_init(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+  let tags = TAGS.get_or_init(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+  let input = comments.replace_all(&input, "");
+  tags.replace_all(&input, "").into_owned()
+}
+
+/// percent encode everything other than the unreserved chars of RFC 3986.
+fn url_encode(input: &str) -> String {
+  let mut output = String::new();
+  for byte in input.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+        output.push(byte as char)
+      },
+      _ => output.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  output
+}
+
+/// the standard base64 alphabet, with padding.
+fn base64(input: &[u8]) -> String {
+  const ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+  let mut output = String::new();
+  for chunk in input.chunks(3) {
+    let bytes = [
+      chunk[0],
+      *chunk.get(1).unwrap_or(&0),
+      *chunk.get(2).unwrap_or(&0),
+    ];
+    let n = u32::from(bytes[0]) << 16
+      | u32::from(bytes[1]) << 8
+      | u32::from(bytes[2]);
+    for i in 0..4 {
+      if i <= chunk.len() {
+        output.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+      } else {
+        output.push('=');
+      }
+    }
+  }
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::RenderOptions;
+
+  fn call(name: &str, input: &str, args: &[&str]) -> String {
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let f = functions().into_iter().find(|f| f.name == name).unwrap();
+    (f.call)(
+      input.to_owned(),
+      &Context::new(&args, &RenderOptions::new()),
+    )
+  }
+
+  #[test]
+  fn cases() {
+    assert_eq!(call("CAPITALIZE", "éCOLE nORMALE", &[]), "École normale");
+    assert_eq!(call("CAPITALIZE", "ßtraße", &[]), "SStraße");
+    assert_eq!(
+      call("TITLE_CASE", "hELLO wORLD-wide", &[]),
+      "Hello World-Wide"
+    );
+    assert_eq!(
+      call("SNAKE_CASE", "XMLHttpRequest v2", &[]),
+      "xml_http_request_v2"
+    );
+    assert_eq!(
+      call("KEBAB_CASE", "Hello Wörld_foo", &[]),
+      "hello-wörld-foo"
+    );
+    assert_eq!(call("CAMEL_CASE", "user_first-NAME", &[]), "userFirstName");
+    assert_eq!(call("LOWERCASE", "ÀB", &[]), "àb");
+  }
+
+  #[test]
+  fn truncate_and_wrap() {
+    assert_eq!(call("TRUNCATE", "Hello World", &["5"]), "Hello…");
+    assert_eq!(call("TRUNCATE", "Hello World", &["6", "..."]), "Hello...");
+    assert_eq!(call("TRUNCATE", "Hello", &["5"]), "Hello");
+    // `e` + combining acute accent is one char.
+    assert_eq!(call("TRUNCATE", "ae\u{301}bc", &["2"]), "ae\u{301}…");
+    assert_eq!(
+      call("WORDWRAP", "the quick brown fox jumps", &["10"]),
+      "the quick\nbrown fox\njumps"
+    );
+    assert_eq!(
+      call("WORDWRAP", "a verylongword b", &["4"]),
+      "a\nverylongword\nb"
+    );
+    assert_eq!(call("INDENT", "a\n\n  b", &["2"]), "  a\n\n    b");
+  }
+
+  #[test]
+  fn transform() {
+    assert_eq!(call("REPLACE", "a-b-c", &["-", "+"]), "a+b+c");
+    assert_eq!(
+      call("SLUG", "  Crème Brûlée, 2 ways! ", &[]),
+      "creme-brulee-2-ways"
+    );
+    assert_eq!(call("SLUG", "Σίγμα ٣", &[]), "σιγμα-٣");
+    assert_eq!(call("REVERSE", "ae\u{301}🇪🇬", &[]), "🇪🇬e\u{301}a");
+    assert_eq!(call("LENGTH", "ae\u{301}🇪🇬", &[]), "3");
+    assert_eq!(
+      call("STRIP_HTML", "<p>Hi <b>there</b><!-- <i>x</i> --></p>", &[]),
+      "Hi there"
+    );
+  }
+
+  #[test]
+  fn encode() {
+    assert_eq!(
+      call("URL_ENCODE", "a b&c=d/é~", &[]),
+      "a%20b%26c%3Dd%2F%C3%A9~"
+    );
+    assert_eq!(call("BASE64", "", &[]), "");
+    assert_eq!(call("BASE64", "f", &[]), "Zg==");
+    assert_eq!(call("BASE64", "fo", &[]), "Zm8=");
+    assert_eq!(call("BASE64", "foo", &[]), "Zm9v");
+    assert_eq!(call("BASE64", "σ", &[]), "z4M=");
+  }
+}
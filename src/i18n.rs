@@ -0,0 +1,855 @@
+use crate::{RenderOptions, Sigma};
+use std::{collections::HashMap, error, fmt, fs, io, path::Path, sync::Arc};
+
+/// The translated messages used by the `t("key", args..)` expressions.
+///
+/// Example:
+/// ```
+/// # use sigma::{MessageCatalog, RenderOptions, Sigma};
+/// # use std::collections::HashMap;
+/// struct Shout;
+///
+/// impl MessageCatalog for Shout {
+///   fn message(&self, _: &str, key: &str, _: &[(&str, &str)]) -> Option<String> {
+///     Some(key.to_uppercase())
+///   }
+/// }
+///
+/// let sigma = Sigma::new("{{ t(\"hello\") }}").catalog(Shout).parse().unwrap();
+/// assert_eq!("HELLO", sigma.render(&HashMap::new()).unwrap());
+/// ```
+pub trait MessageCatalog: Send + Sync {
+  /// the message with that key in that locale, with the arguments, given by
+  /// their names, formatted in. `None` if there is no such message.
+  fn message(
+    &self,
+    locale: &str,
+    key: &str,
+    args: &[(&str, &str)],
+  ) -> Option<String>;
+
+  /// the keys of every message in any locale, so the templates are checked
+  /// for unknown keys at parse time, the default is no keys and no checks.
+  fn keys(&self) -> Vec<&str> {
+    Vec::new()
+  }
+}
+
+/// The plural categories of the CLDR plural rules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+  /// ex: `0` in Arabic.
+  Zero,
+  /// ex: `1` in English.
+  One,
+  /// ex: `2` in Arabic or Hebrew.
+  Two,
+  /// ex: `3` in Polish.
+  Few,
+  /// ex: `5` in Russian.
+  Many,
+  /// every other number.
+  Other,
+}
+
+impl PluralCategory {
+  /// the name of this category, as written in the message variants.
+  pub fn as_str(self) -> &'static str {
+    match self {
+      PluralCategory::Zero => "zero",
+      PluralCategory::One => "one",
+      PluralCategory::Two => "two",
+      PluralCategory::Few => "few",
+      PluralCategory::Many => "many",
+      PluralCategory::Other => "other",
+    }
+  }
+}
+
+/// The plural category of a number in a locale, the number is written as it
+/// would be displayed, so `1` and `1.0` are not the same.
+///
+/// the rules of `ar`, `cs`, `de`, `en`, `es`, `fr`, `he`, `hi`, `it`, `ja`,
+/// `ko`, `nl`, `pl`, `pt`, `ru`, `sv`, `tr`, `uk`, `zh` and some others are
+/// known, any other locale uses the English rules.
+///
+/// Example:
+/// ```
+/// # use sigma::{plural_category, PluralCategory};
+/// assert_eq!(plural_category("en", "1"), PluralCategory::One);
+/// assert_eq!(plural_category("en", "1.0"), PluralCategory::Other);
+/// assert_eq!(plural_category("ru-RU", "22"), PluralCategory::Few);
+/// assert_eq!(plural_category("ar", "0"), PluralCategory::Zero);
+/// ```
+pub fn plural_category(locale: &str, number: &str) -> PluralCategory {
+  use PluralCategory::*;
+  let number = number.trim().trim_start_matches('-');
+  let (int, fraction) = match number.find('.') {
+    Some(dot) => (&number[..dot], &number[dot + 1..]),
+    None => (number, ""),
+  };
+  // the CLDR operands, `i` the integer digits and `v` the number of the
+  // visible fraction digits, `i` is kept small since only its last digits
+  // matter.
+  let i: u64 = int
+    .get(int.len().saturating_sub(6)..)
+    .and_then(|digits| digits.parse().ok())
+    .unwrap_or(0);
+  let v = fraction.len();
+  let n = if v == 0 || fraction.bytes().all(|b| b == b'0') {
+    Some(i)
+  } else {
+    None
+  };
+  let (i10, i100) = (i % 10, i % 100);
+  match language(locale) {
+    "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" => Other,
+    "fr" | "pt" if i <= 1 => One,
+    "fr" | "pt" => Other,
+    "hi" | "bn" | "fa" if i == 0 || n == Some(1) => One,
+    "hi" | "bn" | "fa" => Other,
+    "es" | "el" | "hu" | "tr" if n == Some(1) => One,
+    "es" | "el" | "hu" | "tr" => Other,
+    "he" if i == 1 && v == 0 => One,
+    "he" if i == 2 && v == 0 => Two,
+    "he" => Other,
+    "ar" => match n.map(|n| (n, n % 100)) {
+      Some((0, _)) => Zero,
+      Some((1, _)) => One,
+      Some((2, _)) => Two,
+      Some((_, 3..=10)) => Few,
+      Some((_, 11..=99)) => Many,
+      _ => Other,
+    },
+    "ru" | "uk" | "be" if v != 0 => Other,
+    "ru" | "uk" | "be" => match (i10, i100) {
+      (1, _) if i100 != 11 => One,
+      (2..=4, _) if !(12..=14).contains(&i100) => Few,
+      _ => Many,
+    },
+    "pl" if v != 0 => Other,
+    "pl" if i == 1 => One,
+    "pl" => match (i10, i100) {
+      (2..=4, _) if !(12..=14).contains(&i100) => Few,
+      _ => Many,
+    },
+    "cs" | "sk" if v != 0 => Many,
+    "cs" | "sk" => match i {
+      1 => One,
+      2..=4 => Few,
+      _ => Other,
+    },
+    _ if i == 1 && v == 0 => One,
+    _ => Other,
+  }
+}
+
+/// the render locale when none is set.
+pub(crate) const DEFAULT_LOCALE: &str = "en";
+
+/// `pt-BR` -> `pt`, `zh_Hant` -> `zh`.
+fn language(locale: &str) -> &str {
+  locale.split(['-', '_']).next().unwrap_or(locale)
+}
+
+/// An error in the source of a message catalog.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CatalogError {
+  /// the line of the message, starting from 1.
+  pub line: usize,
+  /// what is wrong with it.
+  pub message: String,
+}
+
+impl fmt::Display for CatalogError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "line {}: {}", self.line, self.message)
+  }
+}
+
+impl error::Error for CatalogError {}
+
+/// A message catalog built in code, the messages are written in the same
+/// syntax as the [`FluentCatalog`] ones.
+///
+/// Example:
+/// ```
+/// # use sigma::{MemoryCatalog, RenderOptions, Sigma};
+/// # use std::collections::HashMap;
+/// let catalog = MemoryCatalog::new()
+///   .insert("en", "welcome", "Welcome, { $name }!")
+///   .insert("fr", "welcome", "Bienvenue, { $name } !");
+/// let sigma = Sigma::new("{{ t(\"welcome\", name) }}")
+///   .catalog(catalog)
+///   .parse()
+///   .unwrap();
+/// let mut bindings = HashMap::new();
+/// bindings.insert("name", "Ada");
+/// assert_eq!("Welcome, Ada!", sigma.render(&bindings).unwrap());
+/// let options = RenderOptions::new().locale("fr-CA");
+/// assert_eq!("Bienvenue, Ada !", sigma.render_with(&bindings, &options).unwrap());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MemoryCatalog {
+  bundle: Bundle,
+}
+
+impl MemoryCatalog {
+  /// Create an empty catalog.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// add a message to a locale.
+  ///
+  /// ## Panics
+  /// if the message is not a valid pattern, ex: an unclosed `{`.
+  pub fn insert(mut self, locale: &str, key: &str, message: &str) -> Self {
+    let pattern = parse_pattern(message)
+      .unwrap_or_else(|e| panic!("invalid message `{}`: {}", key, e));
+    self.bundle.insert(locale, key, pattern);
+    self
+  }
+}
+
+impl MessageCatalog for MemoryCatalog {
+  fn message(
+    &self,
+    locale: &str,
+    key: &str,
+    args: &[(&str, &str)],
+  ) -> Option<String> {
+    self.bundle.message(locale, key, args)
+  }
+
+  fn keys(&self) -> Vec<&str> {
+    self.bundle.keys()
+  }
+}
+
+/// A message catalog loaded from [Fluent](https://projectfluent.org) `.ftl`
+/// files, one or more per locale.
+///
+/// the supported syntax is the messages, the multiline messages, the
+/// `{ $var }` arguments, the `{ -term }` and `{ message }` references, the
+/// `{ "literal" }` strings and the select expressions, where a variant key
+/// is a number, a plural category or a string:
+/// ```text
+/// # a comment
+/// -brand = Sigma
+/// welcome = Welcome to { -brand }, { $name }!
+/// items = { $count ->
+///     [0] no items
+///     [one] one item
+///    *[other] { $count } items
+/// }
+/// ```
+///
+/// Example:
+/// ```
+/// # use sigma::{FluentCatalog, Sigma};
+/// # use std::collections::HashMap;
+/// let catalog = FluentCatalog::new()
+///   .add_resource("en", "items = { $count ->\n  [one] one item\n *[other] { $count } items\n}")
+///   .unwrap();
+/// let sigma = Sigma::new("{{ n: u32 }} = {{ t(\"items\", count = n) }}")
+///   .catalog(catalog)
+///   .parse()
+///   .unwrap();
+/// let mut bindings = HashMap::new();
+/// bindings.insert("n", "3");
+/// assert_eq!("3 = 3 items", sigma.render(&bindings).unwrap());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FluentCatalog {
+  bundle: Bundle,
+}
+
+impl FluentCatalog {
+  /// Create an empty catalog.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// add the messages of a `.ftl` source to a locale, a message already in
+  /// that locale is replaced.
+  pub fn add_resource(
+    mut self,
+    locale: &str,
+    source: &str,
+  ) -> Result<Self, CatalogError> {
+    for (key, pattern) in parse_resource(source)? {
+      self.bundle.insert(locale, &key, pattern);
+    }
+    Ok(self)
+  }
+
+  /// read a `.ftl` file and add its messages to a locale.
+  pub fn load<P: AsRef<Path>>(self, locale: &str, path: P) -> io::Result<Self> {
+    let source = fs::read_to_string(path)?;
+    self
+      .add_resource(locale, &source)
+      .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+  }
+}
+
+impl MessageCatalog for FluentCatalog {
+  fn message(
+    &self,
+    locale: &str,
+    key: &str,
+    args: &[(&str, &str)],
+  ) -> Option<String> {
+    self.bundle.message(locale, key, args)
+  }
+
+  fn keys(&self) -> Vec<&str> {
+    self.bundle.keys()
+  }
+}
+
+/// The catalog of a `Sigma`, compared by its address.
+#[derive(Clone)]
+pub(crate) struct Catalog(pub(crate) Arc<dyn MessageCatalog>);
+
+impl fmt::Debug for Catalog {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_tuple("Catalog").field(&self.0.keys()).finish()
+  }
+}
+
+impl PartialEq for Catalog {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.0, &other.0)
+  }
+}
+
+impl Eq for Catalog {}
+
+impl<'s> Sigma<'s> {
+  /// the message in the render locale, or in its language if the locale has
+  /// a region, `fr-CA` then `fr`, the default locale is `en`.
+  pub(crate) fn translate(
+    &self,
+    key: &str,
+    args: &[(&str, &str)],
+    options: &RenderOptions,
+  ) -> Option<String> {
+    let catalog = &self.catalog.as_ref()?.0;
+    let locale = options.locale.as_deref().unwrap_or(DEFAULT_LOCALE);
+    catalog
+      .message(locale, key, args)
+      .or_else(|| match language(locale) {
+        language if language != locale => catalog.message(language, key, args),
+        _ => None,
+      })
+  }
+}
+
+/// the messages by their locales and keys.
+#[derive(Clone, Debug, Default)]
+struct Bundle {
+  locales: HashMap<String, HashMap<String, Pattern>>,
+}
+
+type Pattern = Vec<Element>;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Element {
+  Text(String),
+  /// `{ $name }`
+  Var(String),
+  /// `{ "text" }`
+  Literal(String),
+  /// `{ -term }` or `{ message }`, a term key starts with `-`.
+  Reference(String),
+  /// `{ $var -> [key] ... *[other] ... }`
+  Select {
+    selector: String,
+    variants: Vec<(String, Pattern)>,
+    default: usize,
+  },
+}
+
+/// how deep the message references could go, so a cycle stops.
+const MAX_DEPTH: usize = 8;
+
+impl Bundle {
+  fn insert(&mut self, locale: &str, key: &str, pattern: Pattern) {
+    self
+      .locales
+      .entry(locale.to_owned())
+      .or_default()
+      .insert(key.to_owned(), pattern);
+  }
+
+  fn keys(&self) -> Vec<&str> {
+    let mut keys: Vec<_> = self
+      .locales
+      .values()
+      .flat_map(|messages| messages.keys().map(String::as_str))
+      .filter(|key| !key.starts_with('-'))
+      .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+  }
+
+  fn message(
+    &self,
+    locale: &str,
+    key: &str,
+    args: &[(&str, &str)],
+  ) -> Option<String> {
+    let messages = self.locales.get(locale)?;
+    let pattern = messages.get(key)?;
+    let mut output = String::new();
+    format(pattern, locale, messages, args, 0, &mut output);
+    Some(output)
+  }
+}
+
+/// format a pattern, like Fluent a missing argument or reference is written
+/// as it is, `{$name}`.
+fn format(
+  pattern: &[Element],
+  locale: &str,
+  messages: &HashMap<String, Pattern>,
+  args: &[(&str, &str)],
+  depth: usize,
+  output: &mut String,
+) {
+  let arg = |name: &str| {
+    args
+      .iter()
+      .find(|(arg, _)| *arg == name)
+      .map(|(_, value)| *value)
+  };
+  for element in pattern {
+    match element {
+      Element::Text(text) | Element::Literal(text) => output.push_str(text),
+      Element::Var(name) => match arg(name) {
+        Some(value) => output.push_str(value),
+        None => output.push_str(&format!("{{${}}}", name)),
+      },
+      Element::Reference(key) => match messages.get(key) {
+        Some(pattern) if depth < MAX_DEPTH => {
+          format(pattern, locale, messages, args, depth + 1, output)
+        },
+        _ => output.push_str(&format!("{{{}}}", key)),
+      },
+      Element::Select {
+        selector,
+        variants,
+        default,
+      } => {
+        let index = arg(selector)
+          .and_then(|value| select(locale, value, variants))
+          .unwrap_or(*default);
+        let pattern = &variants[index].1;
+        format(pattern, locale, messages, args, depth, output);
+      },
+    }
+  }
+}
+
+/// the variant for that value, an exact number first, then its plural
+/// category, then the same string.
+fn select(
+  locale: &str,
+  value: &str,
+  variants: &[(String, Pattern)],
+) -> Option<usize> {
+  let position =
+    |f: &dyn Fn(&str) -> bool| variants.iter().position(|(key, _)| f(key));
+  match value.parse::<f64>() {
+    Ok(number) => {
+      position(&|key| key.parse::<f64>() == Ok(number)).or_else(|| {
+        let category = plural_category(locale, value).as_str();
+        position(&|key| key == category)
+      })
+    },
+    Err(_) => position(&|key| key == value),
+  }
+}
+
+/// parse the messages of a `.ftl` source, a message starts with its key at
+/// the start of a line and goes on in the indented lines after it.
+fn parse_resource(
+  source: &str,
+) -> Result<Vec<(String, Pattern)>, CatalogError> {
+  let lines: Vec<&str> = source.lines().collect();
+  let mut messages = Vec::new();
+  let mut i = 0;
+  while i < lines.len() {
+    let line = lines[i];
+    let start = i + 1;
+    i += 1;
+    if line.trim().is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let error = |message: String| CatalogError {
+      line: start,
+      message,
+    };
+    if line.starts_with(char::is_whitespace) {
+      return Err(error("unexpected indented line".to_owned()));
+    }
+    let (key, value) = line
+      .split_once('=')
+      .ok_or_else(|| error("expected a message, `key = value`".to_owned()))?;
+    let key = key.trim_end();
+    if !is_identifier(key.strip_prefix('-').unwrap_or(key)) {
+      return Err(error(format!("invalid message key `{}`", key)));
+    }
+    // the next lines are a part of the message while they are indented, or
+    // start a variant or close a select expression.
+    let mut continuation = Vec::new();
+    while let Some(next) = lines.get(i) {
+      let trimmed = next.trim_start();
+      let belongs = next.trim().is_empty()
+        || next.starts_with(char::is_whitespace)
+        || trimmed.starts_with(['[', '*', '}']);
+      if !belongs {
+        break;
+      }
+      continuation.push(*next);
+      i += 1;
+    }
+    while continuation
+      .last()
+      .is_some_and(|line| line.trim().is_empty())
+    {
+      continuation.pop();
+    }
+    let indent = continuation
+      .iter()
+      .filter(|line| !line.trim().is_empty())
+      .map(|line| line.len() - line.trim_start().len())
+      .filter(|indent| *indent > 0)
+      .min()
+      .unwrap_or(0);
+    let mut text = value.trim_start().to_owned();
+    for line in continuation {
+      if !text.is_empty() {
+        text.push('\n');
+      }
+      let dedented = line
+        .get(indent..)
+        .filter(|_| line.len() - line.trim_start().len() >= indent);
+      text.push_str(dedented.unwrap_or_else(|| line.trim_start()));
+    }
+    let pattern = parse_pattern(text.trim_end()).map_err(error)?;
+    messages.push((key.to_owned(), pattern));
+  }
+  Ok(messages)
+}
+
+fn is_identifier(name: &str) -> bool {
+  name.starts_with(|c: char| c.is_ascii_alphabetic())
+    && name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// parse the value of a message.
+fn parse_pattern(input: &str) -> Result<Pattern, String> {
+  let mut parser = PatternParser {
+    chars: input.chars().collect(),
+    pos: 0,
+  };
+  parser.pattern(false)
+}
+
+struct PatternParser {
+  chars: Vec<char>,
+  pos: usize,
+}
+
+impl PatternParser {
+  fn peek(&self) -> Option<char> {
+    self.chars.get(self.pos).copied()
+  }
+
+  fn eat(&mut self, expected: &str) -> bool {
+    let end = self.pos + expected.chars().count();
+    let found = self
+      .chars
+      .get(self.pos..end)
+      .is_some_and(|chars| chars.iter().copied().eq(expected.chars()));
+    if found {
+      self.pos = end;
+    }
+    found
+  }
+
+  fn expect(&mut self, expected: &str) -> Result<(), String> {
+    match self.eat(expected) {
+      true => Ok(()),
+      false => Err(format!("expected `{}`", expected)),
+    }
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self.peek().is_some_and(char::is_whitespace) {
+      self.pos += 1;
+    }
+  }
+
+  /// the chars while `f` holds.
+  fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+    let start = self.pos;
+    while self.peek().is_some_and(&f) {
+      self.pos += 1;
+    }
+    self.chars[start..self.pos].iter().collect()
+  }
+
+  fn identifier(&mut self) -> Result<String, String> {
+    let name =
+      self.take_while(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    match is_identifier(&name) {
+      true => Ok(name),
+      false => Err("expected an identifier".to_owned()),
+    }
+  }
+
+  /// text and placeables, a variant pattern ends with its line or with the
+  /// `}` of its select expression.
+  fn pattern(&mut self, variant: bool) -> Result<Pattern, String> {
+    let mut pattern = Vec::new();
+    let mut text = String::new();
+    while let Some(c) = self.peek() {
+      match c {
+        '\n' | '}' if variant => break,
+        '{' => {
+          self.pos += 1;
+          if !text.is_empty() {
+            pattern.push(Element::Text(std::mem::take(&mut text)));
+          }
+          pattern.push(self.placeable()?);
+        },
+        '}' => return Err("unbalanced `}`".to_owned()),
+        c => {
+          text.push(c);
+          self.pos += 1;
+        },
+      }
+    }
+    if !text.is_empty() {
+      pattern.push(Element::Text(text));
+    }
+    Ok(pattern)
+  }
+
+  /// what is inside the `{ }`, after the `{`.
+  fn placeable(&mut self) -> Result<Element, String> {
+    self.skip_whitespace();
+    let element = match self.peek() {
+      Some('$') => {
+        self.pos += 1;
+        let name = self.identifier()?;
+        self.skip_whitespace();
+        if self.eat("->") {
+          return self.select(name);
+        }
+        Element::Var(name)
+      },
+      Some('-') => {
+        self.pos += 1;
+        Element::Reference(format!("-{}", self.identifier()?))
+      },
+      Some('"') => {
+        self.pos += 1;
+        let mut literal = String::new();
+        loop {
+          match self.peek() {
+            Some('"') => break,
+            Some('\\') => {
+              literal.extend(self.chars.get(self.pos + 1));
+              self.pos += 2;
+            },
+            Some('\n') | None => {
+              return Err("unclosed string literal".to_owned())
+            },
+            Some(c) => {
+              literal.push(c);
+              self.pos += 1;
+            },
+          }
+        }
+        self.pos += 1;
+        Element::Literal(literal)
+      },
+      Some(c) if c.is_ascii_digit() => {
+        Element::Literal(self.take_while(|c| c.is_ascii_digit() || c == '.'))
+      },
+      _ => Element::Reference(self.identifier()?),
+    };
+    self.skip_whitespace();
+    self.expect("}")?;
+    Ok(element)
+  }
+
+  /// the variants of a select expression, after the `->`, one per line.
+  fn select(&mut self, selector: String) -> Result<Element, String> {
+    let mut variants = Vec::new();
+    let mut default = None;
+    loop {
+      self.skip_whitespace();
+      if self.eat("}") {
+        break;
+      }
+      if self.eat("*") {
+        if default.is_some() {
+          return Err("a select expression has one default variant".to_owned());
+        }
+        default = Some(variants.len());
+      }
+      self.expect("[")?;
+      let key = self.take_while(|c| c != ']' && c != '\n');
+      self.expect("]")?;
+      while self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+        self.pos += 1;
+      }
+      let mut pattern = self.pattern(true)?;
+      if let Some(Element::Text(text)) = pattern.last_mut() {
+        let trimmed = text.trim_end().len();
+        text.truncate(trimmed);
+      }
+      variants.push((key.trim().to_owned(), pattern));
+    }
+    match default {
+      Some(default) => Ok(Element::Select {
+        selector,
+        variants,
+        default,
+      }),
+      None => {
+        Err("a select expression needs a default `*[...]` variant".to_owned())
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::PluralCategory::*;
+
+  #[test]
+  fn plural_rules() {
+    let categories = |locale: &str, numbers: &[&str]| -> Vec<_> {
+      numbers.iter().map(|n| plural_category(locale, n)).collect()
+    };
+    assert_eq!(
+      categories("en-US", &["0", "1", "2", "1.5"]),
+      vec![Other, One, Other, Other]
+    );
+    assert_eq!(
+      categories("fr", &["0", "1", "1.5", "2"]),
+      vec![One, One, One, Other]
+    );
+    assert_eq!(
+      categories("ru", &["1", "21", "11", "3", "5", "1.5"]),
+      vec![One, One, Many, Few, Many, Other]
+    );
+    assert_eq!(
+      categories("pl", &["1", "22", "12", "25"]),
+      vec![One, Few, Many, Many]
+    );
+    assert_eq!(
+      categories("ar", &["0", "1", "2", "3", "11", "100"]),
+      vec![Zero, One, Two, Few, Many, Other]
+    );
+    assert_eq!(categories("ja", &["1"]), vec![Other]);
+  }
+
+  #[test]
+  fn fluent_resources() {
+    let source = "\
+# the brand name
+-brand = Sigma
+welcome = Welcome to { -brand }, { $name }!
+items = { $count ->
+    [0] no items
+    [one] one item
+   *[other] { $count } items
+}
+notes =
+    first line
+      second line
+braces = { \"{\" } and { 42 }
+";
+    let catalog = FluentCatalog::new().add_resource("en", source).unwrap();
+    assert_eq!(catalog.keys(), vec!["braces", "items", "notes", "welcome"]);
+    let message =
+      |key, args: &[(&str, &str)]| catalog.message("en", key, args).unwrap();
+    assert_eq!(
+      message("welcome", &[("name", "Ada")]),
+      "Welcome to Sigma, Ada!"
+    );
+    assert_eq!(message("welcome", &[]), "Welcome to Sigma, {$name}!");
+    assert_eq!(message("items", &[("count", "0")]), "no items");
+    assert_eq!(message("items", &[("count", "1")]), "one item");
+    assert_eq!(message("items", &[("count", "7")]), "7 items");
+    assert_eq!(message("notes", &[]), "first line\n  second line");
+    assert_eq!(message("braces", &[]), "{ and 42");
+    assert!(catalog.message("fr", "welcome", &[]).is_none());
+    let errors = [
+      ("a = { $x", "line 1: expected `}`"),
+      ("\n  a = b", "line 2: unexpected indented line"),
+      ("a = { $x ->\n  [one] x\n}", "needs a default"),
+      ("a b", "expected a message"),
+    ];
+    for (source, expected) in errors.iter() {
+      let err = FluentCatalog::new().add_resource("en", source).unwrap_err();
+      assert!(err.to_string().contains(expected), "{}", err);
+    }
+  }
+
+  #[test]
+  fn translations() {
+    let catalog = MemoryCatalog::new()
+      .insert("en", "welcome", "Welcome, { $name }!")
+      .insert(
+        "en",
+        "items",
+        "{ $count -> \n [one] one item\n*[other] many }",
+      )
+      .insert(
+        "ar",
+        "items",
+        "{ $count -> \n [two] عنصران\n*[other] عناصر }",
+      )
+      .insert("de", "welcome", "Willkommen, { $name }!");
+    let input = "{{# let double: u8 = n * 2 }}\
+                 {{ t(\"welcome\", name) }} {{ t(\"items\", count = double) }}";
+    let sigma = Sigma::new(input).catalog(catalog.clone()).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("name", "Ada");
+    bindings.insert("n", "1");
+    assert_eq!(sigma.render(&bindings).unwrap(), "Welcome, Ada! many");
+    let options = RenderOptions::new().locale("ar-EG");
+    let err = sigma.render_with(&bindings, &options).unwrap_err();
+    assert!(err.to_string().contains("no `welcome` message in `ar-EG`"));
+    let sigma = Sigma::new("{{ t(\"items\", count = n) }}")
+      .catalog(catalog.clone())
+      .locale("ar")
+      .parse()
+      .unwrap();
+    bindings.insert("n", "2");
+    assert_eq!(sigma.render(&bindings).unwrap(), "عنصران");
+    let parse_error = |input| {
+      Sigma::new(input)
+        .catalog(catalog.clone())
+        .parse()
+        .unwrap_err()
+        .to_string()
+    };
+    assert!(parse_error("{{ t(\"welcom\") }}").contains("did you mean"));
+    assert!(parse_error("{{ t(\"items\", n + 1) }}").contains("named"));
+    let err = Sigma::new("{{ t(\"items\") }}").parse().unwrap_err();
+    assert!(err.to_string().contains("needs a message catalog"));
+  }
+}
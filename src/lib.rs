@@ -14,8 +14,9 @@
 //! sigma is also typed, that means that it has the idea of built-in validators
 //! for your input. and for those how wanna play, it also could be untyped.
 //! also it has a good error checking at parse time of your template.
-//! the only error that could happen in runtime is that the input data fails to
-//! be parsed to your data types in your templates.
+//! the only errors that could happen in runtime is that the input data fails
+//! to be parsed to your data types in your templates, or that you forgot to
+//! bind some variable.
 //! Here is some error examples:
 //! ```ignore
 //! --> 1:49
@@ -103,6 +104,53 @@
 //!  .compile()?;
 //! assert_eq!("Hello SOMEONE", result);
 //! ```
+//! * what about expressions ?
+//! ```ignore
+//! use sigma::Sigma;
+//!
+//! let result = Sigma::new("Total: {{ price * qty: f64 | FORMAT_NUMBER(2) }}") // arithmetic, `~` to join strings, comparisons, `&&`, `||`, `!` and `cond ? a : b`.
+//!  .bind("price", "2.5")
+//!  .bind("qty", "3")
+//!  .parse()
+//!  .map_err(|e| eprintln!("{}", e))? // for pretty printing the error..
+//!  .compile()?;
+//! assert_eq!("Total: 7.50", result);
+//! ```
+//! * computed once, used many times ?
+//! ```ignore
+//! use sigma::Sigma;
+//!
+//! let result = Sigma::new("{{# let total: f64 = price * qty }}{{ total }} / 2 = {{ total / 2 }}") // `{{# set total = ... }}` to change it later.
+//!  .bind("price", "2.5")
+//!  .bind("qty", "4")
+//!  .parse()
+//!  .map_err(|e| eprintln!("{}", e))? // for pretty printing the error..
+//!  .compile()?;
+//! assert_eq!("10 / 2 = 5", result);
+//! ```
+//! * write it once, use it everywhere ?
+//! ```ignore
+//! use sigma::Sigma;
+//!
+//! let result = Sigma::new("{{# macro link(label, url) }}<a href=\"{{ url }}\">{{ label }}</a>{{/ macro }}{{> link(\"Home\", home) }}") // typed parameters too, `link(label, port: u16)`
+//!  .bind("home", "/")
+//!  .parse()
+//!  .map_err(|e| eprintln!("{}", e))? // for pretty printing the error..
+//!  .compile()?;
+//! assert_eq!("<a href=\"/\">Home</a>", result);
+//! ```
+//! * speaks many languages ?
+//! ```ignore
+//! use sigma::{MemoryCatalog, RenderOptions, Sigma};
+//!
+//! let catalog = MemoryCatalog::new() // or a `FluentCatalog` loaded from `.ftl` files, with the plural rules.
+//!  .insert("en", "welcome", "Welcome, { $name }!")
+//!  .insert("fr", "welcome", "Bienvenue, { $name } !");
+//! let sigma = Sigma::new("{{ t(\"welcome\", name) }}").catalog(catalog).parse()?;
+//! let options = RenderOptions::new().locale("fr"); // the locale is chosen per render.
+//! assert_eq!("Bienvenue, Ada !", sigma.render_with(&bindings, &options)?);
+//! // the number and date helpers follow the locale too, `{{ price | NUMBER(2) }}` is `1 234,50` and `{{ day | LOCAL_DATE("long") }}` is `24 février 2019`.
+//! ```
 //! * love macros ?
 //! ```ignore
 //! use sigma::sigma;
@@ -110,19 +158,55 @@
 //! let result = sigma!("Hello {{ username }}", username); // the macro return the result so you can check for compile erros.
 //! assert_eq!("Hello someone", result.unwrap());
 //! ```
+mod constraint;
+mod expr;
+mod format;
+mod function;
+mod helpers;
+mod i18n;
+mod macros;
 mod parser;
+mod schema;
 
-use crate::parser::{Rule, SigmaParser};
+pub use crate::{
+  constraint::{Bounds, Constraint, Number},
+  function::{Context, Function, FunctionCall, HelperModule},
+  i18n::{
+    plural_category, CatalogError, FluentCatalog, MemoryCatalog,
+    MessageCatalog, PluralCategory,
+  },
+};
+
+use crate::{
+  expr::{Expression, Let, Local, Locals},
+  i18n::Catalog,
+  macros::{Call, Macro},
+  parser::{Rule, SigmaParser},
+};
+use chrono::{DateTime, NaiveDate, Utc};
 use pest::{
   error::{Error as PestError, ErrorVariant},
   iterators::{Pair, Pairs},
-  Parser, Span,
+  Parser, Position, Span,
+};
+use regex::Regex;
+use std::{
+  collections::{BTreeSet, HashMap},
+  fmt,
+  net::{Ipv4Addr, Ipv6Addr},
+  ops::Range,
+  sync::{Arc, OnceLock},
 };
-use regex::{NoExpand, Regex};
-use std::collections::HashMap;
 
 type SigmaResult<'a, T> = Result<T, PestError<Rule>>;
 
+/// the names of the built-in data types.
+const DATA_TYPES: [&str; 26] = [
+  "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64",
+  "i128", "isize", "f32", "f64", "str", "nestr", "char", "bool", "email",
+  "url", "uuid", "ipv4", "ipv6", "date", "datetime", "enum",
+];
+
 /// Primitive Data Types
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DataType {
@@ -158,6 +242,22 @@ pub enum DataType {
   ///
   /// ex: `{{ data: i64 }}`
   I64,
+  /// The 128-bit unsigned integer type.
+  ///
+  /// ex: `{{ data: u128 }}`
+  U128,
+  /// The 128-bit signed integer type.
+  ///
+  /// ex: `{{ data: i128 }}`
+  I128,
+  /// The pointer-sized unsigned integer type.
+  ///
+  /// ex: `{{ data: usize }}`
+  Usize,
+  /// The pointer-sized signed integer type.
+  ///
+  /// ex: `{{ data: isize }}`
+  Isize,
   /// The 32-bit floating point type.
   ///
   /// ex: `{{ data: f32 }}`
@@ -170,32 +270,241 @@ pub enum DataType {
   ///
   /// ex: `{{ data: bool }}`
   Bool,
+  /// A single character.
+  ///
+  /// ex: `{{ data: char }}`
+  Char,
   /// String.
   ///
   /// ex: `{{ data: str }}`
   Str,
+  /// String that must not be empty.
+  ///
+  /// ex: `{{ data: nestr }}`
+  NonEmptyStr,
+  /// An email address.
+  ///
+  /// ex: `{{ data: email }}`
+  Email,
+  /// An absolute URL, like `https://example.com`.
+  ///
+  /// ex: `{{ data: url }}`
+  Url,
+  /// A UUID, like `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+  ///
+  /// ex: `{{ data: uuid }}`
+  Uuid,
+  /// An IPv4 address.
+  ///
+  /// ex: `{{ data: ipv4 }}`
+  Ipv4,
+  /// An IPv6 address.
+  ///
+  /// ex: `{{ data: ipv6 }}`
+  Ipv6,
+  /// A calendar date, like `2019-02-24`.
+  ///
+  /// ex: `{{ data: date }}`
+  Date,
+  /// An RFC 3339 date and time, like `2019-02-24T10:30:00Z`.
+  ///
+  /// ex: `{{ data: datetime }}`
+  DateTime,
+  /// One of the listed variants, see [`Constraint::OneOf`].
+  ///
+  /// ex: `{{ data: enum(gold, silver) }}`
+  Enum,
+  /// A user defined data type, see [`Sigma::register_type`].
+  ///
+  /// ex: `{{ data: sku }}`
+  Custom(&'static str),
 }
 
-#[doc(hidden)]
+impl DataType {
+  /// could a value of that type be passed where `other` is expected.
+  ///
+  /// any type is assignable to itself, all the text types (every type other
+  /// than numbers and `bool`) are assignable to `str`, all the numbers are
+  /// assignable to `f64` and `date` and `i64` unix timestamps (in seconds)
+  /// are assignable to `datetime`.
+  pub fn is_assignable_to(&self, other: &DataType) -> bool {
+    use self::DataType::*;
+    match other {
+      _ if self == other => true,
+      Str => !self.is_numeric() && *self != Bool,
+      F64 => self.is_numeric(),
+      DateTime => *self == Date || *self == I64,
+      _ => false,
+    }
+  }
+
+  /// is it one of the integers or floating point types.
+  pub fn is_numeric(&self) -> bool {
+    use self::DataType::*;
+    matches!(
+      self,
+      U8 | I8
+        | U16
+        | I16
+        | U32
+        | I32
+        | U64
+        | I64
+        | U128
+        | I128
+        | Usize
+        | Isize
+        | F32
+        | F64
+    )
+  }
+}
+
+impl fmt::Display for DataType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      DataType::Custom(name) => write!(f, "{}", name),
+      data_type => write!(f, "{:?}", data_type),
+    }
+  }
+}
+
+/// A variable declared in the template, as seen by the parser.
+///
+/// Available after [`Sigma::parse`] through [`Sigma::variables`].
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Variable<'a> {
+  /// the variable name, ex: `username` in `{{ username: str }}`
   pub name: &'a str,
+  /// is it marked with `?`
   pub nullable: bool,
+  /// is it followed by the `:` data type separator
   pub typed: bool,
+  /// the declared data type and where it was declared, for untyped variables
+  /// it's inferred from the first helper function input.
   pub data_type: Option<(DataType, Span<'a>)>,
+  /// the declared data type constraint and where it was declared
+  pub constraint: Option<(Constraint, Span<'a>)>,
+  /// the start and end offsets of the whole `{{ ... }}` pair
   pub location: (usize, usize),
-  pub functions: Vec<(&'a str, Span<'a>)>,
+  /// the helper functions pipeline, in the order they will be called
+  pub functions: Vec<FunctionCall<'a>>,
+  /// where the variable name is in the template
   pub name_span: Option<Span<'a>>,
+  /// the raw `{{ ... }}` pair as written in the template
   pub pair_str: &'a str,
 }
 
-#[doc(hidden)]
+/// A part of the parsed template, in the order they are written.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct Function {
-  pub name: String,
-  pub call: fn(String) -> String,
+enum Node<'a> {
+  /// literal text, rendered as it is.
+  Text(&'a str),
+  /// a `{{ var }}` placeholder, the index of the variable in `Sigma::vars`.
+  Var(usize),
+  /// a `{{ a + b }}` placeholder, or a `{{ local }}` one.
+  Expr(Box<Expression<'a>>),
+  /// a `{{# let local = a + b }}` tag.
+  Let(Box<Let<'a>>),
+  /// a `{{> name(a, b) }}` macro call.
+  Call(Box<Call<'a>>),
 }
 
+/// What the second parsing pass knows about the nodes being parsed.
+struct Scope<'s> {
+  /// the variables by their names, see `Sigma::declarations`.
+  declared: HashMap<&'s str, Variable<'s>>,
+  /// the indices in `Sigma::vars` of the next `var_pair`s.
+  vars: Range<usize>,
+  /// the indices in `Sigma::macros` of the next `macro_def`s.
+  macros: Range<usize>,
+  /// the local variables in scope, the last one shadows the others.
+  locals: Vec<Local<'s>>,
+  /// the macro whose body is being parsed.
+  caller: Option<usize>,
+  /// the errors found so far, see `Sigma::parse_all`.
+  errors: Vec<PestError<Rule>>,
+}
+
+/// What to do with a variable that has no binded value at render time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Unbinded {
+  /// fail with an `unbinded variable` error.
+  #[default]
+  Error,
+  /// replace the variable with an empty string, like a nullable one.
+  Empty,
+  /// leave the `{{ ... }}` tag in the output as it's written.
+  Verbatim,
+}
+
+/// Options used while rendering a parsed template.
+///
+/// Example:
+/// ```
+/// # use sigma::{RenderOptions, Sigma, Unbinded};
+/// # use std::collections::HashMap;
+/// let sigma = Sigma::new("Hello {{ username }}").parse().unwrap();
+/// let options = RenderOptions::new().unbinded(Unbinded::Verbatim);
+/// let result = sigma.render_with(&HashMap::new(), &options).unwrap();
+/// assert_eq!("Hello {{ username }}", result);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderOptions {
+  unbinded: Unbinded,
+  strict: bool,
+  now: Option<DateTime<Utc>>,
+  locale: Option<String>,
+}
+
+impl RenderOptions {
+  /// Create the default options, that errors on unbinded variables.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// what to do with the unbinded variables.
+  pub fn unbinded(mut self, unbinded: Unbinded) -> Self {
+    self.unbinded = unbinded;
+    self
+  }
+
+  /// fail on any binding that is not used in the template.
+  pub fn strict(mut self) -> Self {
+    self.strict = true;
+    self
+  }
+
+  /// render as if it's that time now, used by the helpers like
+  /// `RELATIVE_TIME`, the default is the current time.
+  pub fn now(mut self, now: DateTime<Utc>) -> Self {
+    self.now = Some(now);
+    self
+  }
+
+  /// render in that locale, ex: `fr` or `pt-BR`, used by the `t("key")`
+  /// messages and the number and date helpers, the default is `en`.
+  pub fn locale(mut self, locale: &str) -> Self {
+    self.locale = Some(locale.to_owned());
+    self
+  }
+}
+
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+pub struct CustomType {
+  pub name: &'static str,
+  pub validate: fn(&str) -> bool,
+}
+
+impl PartialEq for CustomType {
+  fn eq(&self, other: &Self) -> bool {
+    self.name == other.name
+  }
+}
+
+impl Eq for CustomType {}
+
 /// Sigma, Template Language made simple !
 ///
 /// Example:
@@ -214,12 +523,23 @@ pub struct Function {
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Sigma<'s> {
-  vars: HashMap<&'s str, Variable<'s>>,
+  /// every variable in the template, a variable used in many placeholders
+  /// is there many times.
+  vars: Vec<Variable<'s>>,
+  nodes: Vec<Node<'s>>,
+  /// every macro definition, in the order they are written.
+  macros: Vec<Macro<'s>>,
+  /// the number of local variables slots.
+  locals: usize,
   registry: HashMap<&'s str, &'s str>,
   input: &'s str,
   is_parsed: bool,
-  ignore_unbinded: bool,
-  functions: HashMap<&'s str, Function>,
+  options: RenderOptions,
+  functions: HashMap<String, Function>,
+  /// the flat names of the built-in helpers, `TRIM` for `STR::TRIM`.
+  aliases: HashMap<String, String>,
+  types: HashMap<&'static str, CustomType>,
+  catalog: Option<Catalog>,
 }
 
 impl<'s> Sigma<'s> {
@@ -227,23 +547,29 @@ impl<'s> Sigma<'s> {
   pub fn new(input: &'s str) -> Self {
     let sigma = Self {
       input,
-      vars: HashMap::new(),
+      vars: Vec::new(),
+      nodes: Vec::new(),
+      macros: Vec::new(),
+      locals: 0,
       functions: HashMap::new(),
+      aliases: HashMap::new(),
+      types: HashMap::new(),
       is_parsed: false,
-      ignore_unbinded: false,
+      options: RenderOptions::default(),
       registry: HashMap::new(),
+      catalog: None,
     };
 
-    let sigma = sigma.register_fn("UPPERCASE", |input| input.to_uppercase());
-    let sigma =
-      sigma.register_fn("TRIM_END", |input| input.trim_end().to_owned());
-    let sigma =
-      sigma.register_fn("TRIM_START", |input| input.trim_start().to_owned());
-    let sigma =
-      sigma.register_fn("TRIM_START", |input| input.trim_start().to_owned());
-    let sigma = sigma.register_fn("TRIM", |input| input.trim().to_owned());
-
-    sigma.register_fn("LOWERRCASE", |input| input.to_lowercase())
+    let mut sigma = helpers::modules().into_iter().fold(sigma, Sigma::import);
+    sigma.aliases = sigma
+      .functions
+      .keys()
+      .filter_map(|name| {
+        let (_, flat) = name.split_once("::")?;
+        Some((flat.to_owned(), name.clone()))
+      })
+      .collect();
+    sigma
   }
 
   /// bind some key in the template for some value
@@ -264,39 +590,471 @@ impl<'s> Sigma<'s> {
     self
   }
 
-  /// ignore parse error for unbinded variables
-  pub fn ignore_unbinded(mut self) -> Self {
-    self.ignore_unbinded = true;
+  /// leave the unbinded variables as they are in the output instead of
+  /// failing to render.
+  ///
+  /// same as `.unbinded(Unbinded::Verbatim)`
+  pub fn ignore_unbinded(self) -> Self {
+    self.unbinded(Unbinded::Verbatim)
+  }
+
+  /// what to do with the unbinded variables when rendering the template.
+  pub fn unbinded(mut self, unbinded: Unbinded) -> Self {
+    self.options.unbinded = unbinded;
+    self
+  }
+
+  /// reject any binding that is not used in the template, this helps
+  /// catching typos in the binded keys.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// let result = Sigma::new("Hello {{ username? }}")
+  ///   .bind("usrname", "someone")
+  ///   .strict()
+  ///   .parse()
+  ///   .unwrap()
+  ///   .compile();
+  /// let err = result.unwrap_err().to_string();
+  /// assert!(err.contains("unused binding: `usrname` did you mean: `username` ?"));
+  /// ```
+  pub fn strict(mut self) -> Self {
+    self.options.strict = true;
+    self
+  }
+
+  /// render in that locale, see [`RenderOptions::locale`].
+  pub fn locale(mut self, locale: &str) -> Self {
+    self.options = self.options.locale(locale);
+    self
+  }
+
+  /// use that catalog for the `{{ t("key", args..) }}` messages, it must be
+  /// set before parsing, so the message keys are checked.
+  ///
+  /// see [`MemoryCatalog`] and [`FluentCatalog`].
+  pub fn catalog<C: MessageCatalog + 'static>(mut self, catalog: C) -> Self {
+    self.catalog = Some(Catalog(Arc::new(catalog)));
     self
   }
 
-  /// register a helper function
+  /// register a helper function that takes a `str` and returns a `str`
   ///
   /// The Function Name Must be in UPPERCASE
   pub fn register_fn(
-    mut self,
+    self,
     func_name: &'static str,
     func: fn(String) -> String,
   ) -> Self {
-    self.functions.insert(
-      func_name,
-      Function {
-        name: func_name.to_uppercase(),
-        call: func,
+    self.register_typed_fn(func_name, DataType::Str, DataType::Str, func)
+  }
+
+  /// register a helper function that takes a value of the `input` data type
+  /// and returns a value of the `output` data type, the helpers pipeline is
+  /// type checked at parse time.
+  ///
+  /// The Function Name Must be in UPPERCASE
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::{DataType, Sigma};
+  /// let result = Sigma::new("{{ n: u32 | DOUBLE | DOUBLE }}")
+  ///   .register_typed_fn("DOUBLE", DataType::U32, DataType::U32, |input| {
+  ///     (input.parse::<u32>().unwrap() * 2).to_string()
+  ///   })
+  ///   .bind("n", "2")
+  ///   .parse()
+  ///   .unwrap()
+  ///   .compile();
+  /// assert_eq!("8", result.unwrap());
+  /// ```
+  pub fn register_typed_fn(
+    self,
+    func_name: &'static str,
+    input: DataType,
+    output: DataType,
+    func: fn(String) -> String,
+  ) -> Self {
+    self.register(Function::new(func_name, input, output, move |input, _| {
+      func(input)
+    }))
+  }
+
+  /// register a helper function, see [`Function`].
+  pub fn register(mut self, function: Function) -> Self {
+    self.functions.insert(function.name.clone(), function);
+    self
+  }
+
+  /// register all the helper functions of a module, they are called in the
+  /// template as `MODULE::FUNCTION`, see [`HelperModule`].
+  ///
+  /// the built-in helpers are registered under the `STR`, `NUM` and `DATE`
+  /// modules, ex: `STR::TRIM`, and could also be called by their flat names,
+  /// ex: `TRIM`, unless a helper with that name is registered.
+  pub fn import<M: HelperModule>(self, module: M) -> Self {
+    let prefix = module.name().to_uppercase();
+    module
+      .functions()
+      .into_iter()
+      .fold(self, |sigma, mut function| {
+        function.name = format!("{}::{}", prefix, function.name);
+        sigma.register(function)
+      })
+  }
+
+  /// register a data type, the value is valid when the validator returns
+  /// `true`.
+  ///
+  /// The Type Name Must be in lowercase, and it can't shadow a built-in data
+  /// type.
+  ///
+  /// ## Panics
+  /// this will panic if the name is not like `sku` or `iso_code2`, or if it's
+  /// a built-in data type like `str`.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// # use std::collections::HashMap;
+  /// let sigma = Sigma::new("{{ code: sku }}")
+  ///   .register_type("sku", |input| {
+  ///     input.len() == 8 && input.chars().all(|c| c.is_ascii_alphanumeric())
+  ///   })
+  ///   .parse()
+  ///   .unwrap();
+  /// let mut bindings = HashMap::new();
+  /// bindings.insert("code", "AB12CD34");
+  /// assert!(sigma.render(&bindings).is_ok());
+  /// bindings.insert("code", "AB12");
+  /// assert!(sigma.render(&bindings).is_err());
+  /// ```
+  pub fn register_type(
+    mut self,
+    type_name: &'static str,
+    validator: fn(&str) -> bool,
+  ) -> Self {
+    let mut chars = type_name.chars();
+    let well_formed = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+      && chars
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    assert!(
+      well_formed,
+      "the data type `{}` must be in lowercase, like `sku` or `iso_code2`",
+      type_name
+    );
+    assert!(
+      !DATA_TYPES.contains(&type_name),
+      "the data type `{}` cannot shadow the built-in one",
+      type_name
+    );
+    self.types.insert(
+      type_name,
+      CustomType {
+        name: type_name,
+        validate: validator,
       },
     );
     self
   }
 
   /// Parse the template before compiling it to ensure no runtime erros.
-  pub fn parse(mut self) -> SigmaResult<'s, Self> {
-    for sigma in SigmaParser::parse(Rule::sigma, &self.input)? {
-      if sigma.as_rule() == Rule::var_pair {
-        self.parse_var_pair(sigma)?;
+  pub fn parse(self) -> SigmaResult<'s, Self> {
+    self.parse_all().map_err(|mut errors| errors.remove(0))
+  }
+
+  /// Parse the template like `parse` but report every error instead of the
+  /// first one, handy to check many templates at once.
+  ///
+  /// The errors of a placeholder that depends on a broken one, like the
+  /// expressions using a variable whose declaration is wrong, are reported
+  /// once the first ones are fixed.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// let errors = Sigma::new("{{ id: u9 }} {{ name | TRIMM }}")
+  ///   .parse_all()
+  ///   .unwrap_err();
+  /// assert_eq!(errors.len(), 2);
+  /// ```
+  pub fn parse_all(mut self) -> Result<Self, Vec<PestError<Rule>>> {
+    self.vars.clear();
+    self.macros.clear();
+    self.locals = 0;
+    let pairs: Vec<_> = SigmaParser::parse(Rule::sigma, self.input)
+      .map_err(|e| vec![e])?
+      .filter(|pair| pair.as_rule() != Rule::EOI)
+      .collect();
+    // the variables and the macros first, so an expression could use the data
+    // type of a variable declared after it, and a macro could be called
+    // before its definition.
+    let mut errors = Vec::new();
+    self.declare(&pairs, &mut Vec::new(), &mut errors);
+    if !errors.is_empty() {
+      return Err(errors);
+    }
+    let mut scope = Scope {
+      declared: self.declarations(),
+      vars: 0..self.vars.len(),
+      macros: 0..self.macros.len(),
+      locals: Vec::new(),
+      caller: None,
+      errors: Vec::new(),
+    };
+    let nodes = self.parse_nodes(pairs, 0..self.input.len(), &mut scope);
+    match nodes {
+      Ok(nodes) if scope.errors.is_empty() => {
+        self.nodes = nodes;
+        self.is_parsed = true;
+        Ok(self)
+      },
+      Ok(_) => Err(scope.errors),
+      Err(e) => {
+        scope.errors.push(e);
+        Err(scope.errors)
+      },
+    }
+  }
+
+  /// the first parsing pass, parse the `var_pair`s and the macros
+  /// signatures, `locals` has the names of the local variables in scope.
+  fn declare(
+    &mut self,
+    pairs: &[Pair<'s, Rule>],
+    locals: &mut Vec<&'s str>,
+    errors: &mut Vec<PestError<Rule>>,
+  ) {
+    for pair in pairs {
+      match pair.as_rule() {
+        Rule::var_pair if !locals.contains(&var_pair_name(pair)) => {
+          match self.parse_var_pair(pair.clone()) {
+            Ok(var) => self.vars.push(var),
+            Err(e) => errors.push(e),
+          }
+        },
+        Rule::let_pair => {
+          locals.push(pair.clone().into_inner().nth(2).unwrap().as_str());
+        },
+        Rule::macro_def => {
+          let mut inner = pair.clone().into_inner();
+          // without its parameters the body would only report more errors.
+          let definition = match self.parse_macro_open(inner.next().unwrap()) {
+            Ok(definition) => definition,
+            Err(e) => {
+              errors.push(e);
+              continue;
+            },
+          };
+          let mut params = definition.params.iter().map(|p| p.name).collect();
+          self.macros.push(definition);
+          let body: Vec<_> = inner.next().unwrap().into_inner().collect();
+          self.declare(&body, &mut params, errors);
+        },
+        Rule::macro_open => errors.push(expr::error(
+          "unclosed macro, close it with `{{/ macro }}`, macros cannot be \
+           nested"
+            .to_owned(),
+          pair.as_span(),
+        )),
+        Rule::macro_close => errors.push(expr::error(
+          "there is no macro to close".to_owned(),
+          pair.as_span(),
+        )),
+        _ => {},
+      }
+    }
+  }
+
+  /// the second parsing pass, type check the `pairs` of that `range` of the
+  /// input, and turn them with the text between them into nodes.
+  ///
+  /// the errors are collected in `scope.errors`, but a broken `let` stops the
+  /// pass since the next pairs could use its local variable.
+  fn parse_nodes(
+    &mut self,
+    pairs: Vec<Pair<'s, Rule>>,
+    range: Range<usize>,
+    scope: &mut Scope<'s>,
+  ) -> SigmaResult<'s, Vec<Node<'s>>> {
+    let mut nodes = Vec::new();
+    let mut end = range.start;
+    for pair in pairs {
+      let span = pair.as_span();
+      if span.start() > end {
+        nodes.push(Node::Text(&self.input[end..span.start()]));
+      }
+      end = span.end();
+      let node = match pair.as_rule() {
+        Rule::var_pair => {
+          if expr::find_local(&scope.locals, var_pair_name(&pair)).is_some() {
+            self
+              .parse_local_pair(pair, &mut scope.declared, &scope.locals)
+              .map(|expression| Node::Expr(Box::new(expression)))
+          } else {
+            Ok(Node::Var(scope.vars.next().unwrap()))
+          }
+        },
+        Rule::let_pair => {
+          let tag = self.parse_let_pair(
+            pair,
+            &mut scope.declared,
+            &mut scope.locals,
+          )?;
+          Ok(Node::Let(Box::new(tag)))
+        },
+        Rule::call_pair => self
+          .parse_call_pair(pair, scope)
+          .map(|call| Node::Call(Box::new(call))),
+        // a definition renders nothing.
+        Rule::macro_def => {
+          if let Err(e) = self.parse_macro_body(pair, scope) {
+            scope.errors.push(e);
+          }
+          continue;
+        },
+        _ => self
+          .parse_expr_pair(pair, &mut scope.declared, &scope.locals)
+          .map(|expression| Node::Expr(Box::new(expression))),
+      };
+      match node {
+        Ok(node) => nodes.push(node),
+        Err(e) => scope.errors.push(e),
+      }
+    }
+    if end < range.end {
+      nodes.push(Node::Text(&self.input[end..range.end]));
+    }
+    Ok(nodes)
+  }
+
+  /// the variables by their names, a typed variable is preferred over the
+  /// other ones with the same name.
+  fn declarations(&self) -> HashMap<&'s str, Variable<'s>> {
+    let mut declared: HashMap<_, Variable> = HashMap::new();
+    for var in &self.vars {
+      match declared.get(var.name) {
+        Some(other) if other.typed || !var.typed => {},
+        _ => {
+          declared.insert(var.name, var.clone());
+        },
       }
     }
-    self.is_parsed = true;
-    Ok(self)
+    declared
+  }
+
+  /// All the variables used in the template, ordered by where they first
+  /// appear.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::{Sigma, DataType};
+  /// let sigma = Sigma::new("Hi {{ name: str | TRIM }}, {{ age?: u8 }}")
+  ///   .bind("name", "someone")
+  ///   .parse()
+  ///   .unwrap();
+  /// let vars = sigma.variables();
+  /// assert_eq!(vars[0].name, "name");
+  /// assert_eq!(vars[0].functions[0].name, "TRIM");
+  /// assert_eq!(vars[1].data_type.as_ref().unwrap().0, DataType::U8);
+  /// assert!(vars[1].nullable);
+  /// ```
+  pub fn variables(&self) -> Vec<&Variable<'s>> {
+    let mut vars: Vec<_> = self.vars.iter().collect();
+    vars.sort_by_key(|var| var.location.0);
+    let mut seen = BTreeSet::new();
+    vars.retain(|var| seen.insert(var.name));
+    vars
+  }
+
+  /// The names of all the helper functions used in the template.
+  pub fn helpers(&self) -> BTreeSet<&'s str> {
+    let bodies = self.macros.iter().flat_map(|m| &m.body);
+    let expressions =
+      self
+        .nodes
+        .iter()
+        .chain(bodies)
+        .filter_map(|node| match node {
+          Node::Expr(expression) => Some(&expression.functions),
+          Node::Let(tag) => Some(&tag.expression.functions),
+          _ => None,
+        });
+    self
+      .vars
+      .iter()
+      .map(|var| &var.functions)
+      .chain(expressions)
+      .flat_map(|functions| functions.iter().map(|f| f.name))
+      .collect()
+  }
+
+  /// All the helper functions that could be used in the template, the
+  /// built-in ones, the registered ones and the ones of the imported
+  /// modules, ordered by name.
+  ///
+  /// the built-in helpers are listed once, by their module names, see
+  /// [`Sigma::import`] and [`Sigma::aliases`].
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// let sigma = Sigma::new("");
+  /// let names: Vec<_> = sigma.functions().iter().map(|f| f.name()).collect();
+  /// assert!(names.contains(&"STR::TRIM") && !names.contains(&"TRIM"));
+  /// ```
+  pub fn functions(&self) -> Vec<&Function> {
+    let mut functions: Vec<_> = self.functions.values().collect();
+    functions.sort_by(|a, b| a.name().cmp(b.name()));
+    functions
+  }
+
+  /// The flat names the built-in helpers could also be called by, with the
+  /// helper of each one, ordered by name, a registered helper with the same
+  /// name hides the alias.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// let sigma = Sigma::new("");
+  /// let aliases = sigma.aliases();
+  /// let trim = aliases.iter().find(|(name, _)| *name == "TRIM");
+  /// assert_eq!(trim.unwrap().1.name(), "STR::TRIM");
+  /// ```
+  pub fn aliases(&self) -> Vec<(&str, &Function)> {
+    let mut aliases: Vec<_> = self
+      .aliases
+      .iter()
+      .filter(|(alias, _)| !self.functions.contains_key(*alias))
+      .map(|(alias, name)| (alias.as_str(), &self.functions[name]))
+      .collect();
+    aliases.sort_by(|a, b| a.0.cmp(b.0));
+    aliases
+  }
+
+  /// The names of the data types that could be used in the template, the
+  /// built-in ones then the registered ones.
+  pub fn data_types(&self) -> Vec<&'static str> {
+    let mut custom: Vec<_> = self.types.keys().copied().collect();
+    custom.sort_unstable();
+    DATA_TYPES.iter().copied().chain(custom).collect()
+  }
+
+  /// The names of the macros defined in the template and where the names are
+  /// in their definitions.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// let sigma = Sigma::new("{{# macro hi(name) }}Hi {{ name }}{{/ macro }}")
+  ///   .parse()
+  ///   .unwrap();
+  /// let (name, span) = sigma.macros()[0];
+  /// assert_eq!((name, span.start()), ("hi", 10));
+  /// ```
+  pub fn macros(&self) -> Vec<(&'s str, Span<'s>)> {
+    self.macros.iter().map(|m| (m.name, m.name_span)).collect()
   }
 
   /// Compile the template with the binded values
@@ -305,32 +1063,238 @@ impl<'s> Sigma<'s> {
   /// this will panic if the current template
   /// not parsed yet.
   pub fn compile(self) -> SigmaResult<'s, String> {
+    self.render(&self.registry)
+  }
+
+  /// Render the parsed template with a set of bindings, so the same parsed
+  /// template could be used again with other values.
+  ///
+  /// ## Panics
+  /// this will panic if the current template
+  /// not parsed yet.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// # use std::collections::HashMap;
+  /// let sigma = Sigma::new("Hello {{ username }}").parse().unwrap();
+  /// let mut bindings = HashMap::new();
+  /// bindings.insert("username", "someone");
+  /// assert_eq!("Hello someone", sigma.render(&bindings).unwrap());
+  /// bindings.insert("username", "sigma");
+  /// assert_eq!("Hello sigma", sigma.render(&bindings).unwrap());
+  /// ```
+  pub fn render(
+    &self,
+    bindings: &HashMap<&str, &str>,
+  ) -> SigmaResult<'s, String> {
+    self.render_with(bindings, &self.options)
+  }
+
+  /// Render the parsed template with a set of bindings and options.
+  ///
+  /// ## Panics
+  /// this will panic if the current template
+  /// not parsed yet.
+  pub fn render_with(
+    &self,
+    bindings: &HashMap<&str, &str>,
+    options: &RenderOptions,
+  ) -> SigmaResult<'s, String> {
     assert!(self.is_parsed, "The template must be parsed first");
-    let mut output = self.input.to_owned(); // copy the input
-    for var in self.vars.values() {
-      let var_regex = Regex::new(&regex::escape(var.pair_str)).unwrap();
-      if let Some(value) = self.registry.get(var.name) {
-        let mut current_data = (*value).to_owned();
-        for function in &var.functions {
-          let f = &self.functions[&function.0]; // we are sure it will be there.
-          current_data = (f.call)(current_data);
-        }
-        self.validate_data_type(&var, &current_data)?;
-        output = var_regex
-          .replace_all(&output, NoExpand(&current_data))
-          .to_string();
-      } else if var.nullable {
-        // it must be nullable then
-        output = var_regex.replace_all(&output, NoExpand("")).to_string();
+    if options.strict {
+      if let Some(e) = self.unused_bindings(bindings).into_iter().next() {
+        return Err(e);
       }
     }
+    let mut output = String::with_capacity(self.input.len());
+    let mut locals = vec![None; self.locals];
+    for node in &self.nodes {
+      self.render_node(node, bindings, options, &mut locals, &mut output)?;
+    }
     Ok(output)
   }
 
+  fn render_node(
+    &self,
+    node: &Node<'s>,
+    bindings: &HashMap<&str, &str>,
+    options: &RenderOptions,
+    locals: &mut Locals,
+    output: &mut String,
+  ) -> SigmaResult<'s, ()> {
+    match node {
+      Node::Text(text) => output.push_str(text),
+      Node::Var(index) => {
+        let var = &self.vars[*index];
+        if let Some(value) = bindings.get(var.name) {
+          output.push_str(&self.call_functions(var, value, options)?);
+        } else if !var.nullable {
+          match options.unbinded {
+            Unbinded::Error => {
+              return Err(unbinded_error(var, bindings.keys()))
+            },
+            Unbinded::Empty => {},
+            Unbinded::Verbatim => output.push_str(var.pair_str),
+          }
+        }
+      },
+      Node::Expr(expression) => {
+        self.render_expression(expression, bindings, options, locals, output)?
+      },
+      Node::Let(tag) => self.render_let(tag, bindings, options, locals)?,
+      Node::Call(call) => {
+        self.render_call(call, bindings, options, locals, output)?
+      },
+    }
+    Ok(())
+  }
+
+  /// Check a set of bindings against the parsed template without compiling
+  /// it, reporting every invalid or missing value at once.
+  ///
+  /// ## Panics
+  /// this will panic if the current template
+  /// not parsed yet.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// # use std::collections::HashMap;
+  /// let sigma = Sigma::new("{{ id: u32 }} {{ name?: str }} {{ age: u8 }}")
+  ///   .parse()
+  ///   .unwrap();
+  /// let mut bindings = HashMap::new();
+  /// bindings.insert("id", "one");
+  /// bindings.insert("age", "300");
+  /// let errors = sigma.check(&bindings).unwrap_err();
+  /// assert_eq!(errors.len(), 2);
+  /// ```
+  pub fn check(
+    &self,
+    bindings: &HashMap<&str, &str>,
+  ) -> Result<(), Vec<PestError<Rule>>> {
+    assert!(self.is_parsed, "The template must be parsed first");
+    let mut errors = Vec::new();
+    if self.options.strict {
+      errors.extend(self.unused_bindings(bindings));
+    }
+    let mut output = String::new();
+    let mut locals = vec![None; self.locals];
+    for node in &self.nodes {
+      let options = &self.options;
+      if let Err(e) =
+        self.render_node(node, bindings, options, &mut locals, &mut output)
+      {
+        // the same variable could be in many placeholders.
+        if errors.iter().all(|other| other.variant != e.variant) {
+          errors.push(e);
+        }
+      }
+    }
+    if errors.is_empty() {
+      Ok(())
+    } else {
+      Err(errors)
+    }
+  }
+
+  /// report every binding that has no variable in the template.
+  fn unused_bindings(
+    &self,
+    bindings: &HashMap<&str, &str>,
+  ) -> Vec<PestError<Rule>> {
+    let mut unused: Vec<_> = bindings
+      .keys()
+      .filter(|key| self.vars.iter().all(|var| var.name != **key))
+      .collect();
+    unused.sort();
+    unused
+      .into_iter()
+      .map(|key| {
+        let names = self.vars.iter().map(|var| var.name);
+        let extra_help = parser::did_you_mean(key, names)
+          .map(|matches| format!(" did you mean: `{}` ?", matches))
+          .unwrap_or_default();
+        PestError::new_from_pos(
+          ErrorVariant::CustomError {
+            message: format!("unused binding: `{}`{}", key, extra_help),
+          },
+          Position::from_start(self.input),
+        )
+      })
+      .collect()
+  }
+
+  /// validate the variable value against its data type and run it through
+  /// its helpers pipeline.
+  fn call_functions(
+    &self,
+    var: &Variable,
+    value: &str,
+    options: &RenderOptions,
+  ) -> SigmaResult<'s, String> {
+    self.validate_data_type(var, value)?;
+    self.run_functions(&var.functions, value.to_owned(), options)
+  }
+
+  /// could the value be passed to the first helper, an `i64` timestamp could
+  /// be out of the `datetime` range.
+  pub(crate) fn fits_functions(
+    &self,
+    functions: &[FunctionCall],
+    value: &str,
+  ) -> bool {
+    match functions.first() {
+      Some(function)
+        if self.resolve(function.name).unwrap().input == DataType::DateTime =>
+      {
+        helpers::date::try_parse(value).is_some()
+      },
+      _ => true,
+    }
+  }
+
+  /// run the value through a helpers pipeline, a `datetime` output could be
+  /// out of the range the next helper could read, ex: past the year 9999.
+  fn run_functions(
+    &self,
+    functions: &[FunctionCall],
+    mut value: String,
+    options: &RenderOptions,
+  ) -> SigmaResult<'s, String> {
+    for function in functions {
+      let f = self.resolve(function.name).unwrap(); // checked at parse time.
+      if f.input == DataType::DateTime
+        && helpers::date::try_parse(&value).is_none()
+      {
+        return Err(PestError::new_from_span(
+          ErrorVariant::CustomError {
+            message: format!(
+              "cannot parse `{}` into `{}` for helper `{}`",
+              preview(&value),
+              DataType::DateTime,
+              function.name
+            ),
+          },
+          function.span,
+        ));
+      }
+      let cx = Context::new(&function.args, options);
+      value = (f.call)(value, &cx);
+    }
+    Ok(value)
+  }
+
   // TODO: Refactor this function
-  fn parse_var_pair(&mut self, pair: Pair<'s, Rule>) -> SigmaResult<()> {
-    let mut variable = Variable::default();
-    variable.pair_str = pair.as_str();
+  fn parse_var_pair(
+    &self,
+    pair: Pair<'s, Rule>,
+  ) -> SigmaResult<'s, Variable<'s>> {
+    let mut variable = Variable {
+      pair_str: pair.as_str(),
+      ..Default::default()
+    };
     let mut inner_rules = pair.into_inner();
     let open_pairs = inner_rules.next().unwrap();
     let var = inner_rules.next().unwrap();
@@ -353,9 +1317,27 @@ impl<'s> Sigma<'s> {
           variable.data_type =
             Some((self.parse_data_type(&data_type)?, data_type.as_span()));
         },
+        Rule::constraint => {
+          let span = var_rules.as_span();
+          // the grammar only allows a constraint after a data type.
+          let data_type = &variable.data_type.as_ref().unwrap().0;
+          let constraint = constraint::parse_constraint(data_type, var_rules)?;
+          variable.constraint = Some((constraint, span));
+        },
         _ => {},
       };
     }
+    if let Some((DataType::Enum, span)) = variable.data_type {
+      if variable.constraint.is_none() {
+        return Err(PestError::new_from_span(
+          ErrorVariant::CustomError {
+            message: "missing enum variants, ex: `enum(gold, silver)`"
+              .to_owned(),
+          },
+          span,
+        ));
+      }
+    }
     // data type check
     if variable.typed && variable.data_type.is_none() {
       return Err(PestError::new_from_span(
@@ -363,36 +1345,12 @@ impl<'s> Sigma<'s> {
           positives: vec![Rule::data_type],
           negatives: vec![],
         },
-        variable.name_span.clone().unwrap(),
+        variable.name_span.unwrap(),
       ));
     }
     let mut variable = self.parse_function(inner_rules, variable)?;
     variable.location = (open_pairs.as_span().start(), variable.location.1);
-    // check if we have a back value for this variable ?
-    if !self.registry.contains_key(variable.name)
-      && !variable.nullable
-      && !self.ignore_unbinded
-    {
-      let extra_help;
-      if let Some(matches) =
-        parser::did_you_mean(variable.name, self.registry.keys())
-      {
-        extra_help = format!("did you mean: `{}` ?", matches);
-      } else {
-        extra_help = "consider adding a bind for it".to_owned();
-      }
-      return Err(PestError::new_from_span(
-        ErrorVariant::CustomError {
-          message: format!(
-            "unbinded variable: `{}` {}",
-            variable.name, extra_help
-          ),
-        },
-        variable.name_span.clone().unwrap(),
-      ));
-    }
-    self.vars.insert(variable.name, variable);
-    Ok(())
+    Ok(variable)
   }
 
   #[inline(always)]
@@ -411,15 +1369,27 @@ impl<'s> Sigma<'s> {
       "i32" => I32,
       "u64" => U64,
       "i64" => I64,
+      "u128" => U128,
+      "i128" => I128,
+      "usize" => Usize,
+      "isize" => Isize,
       "f32" => F32,
       "f64" => F64,
       "bool" => Bool,
+      "char" => Char,
       "str" => Str,
+      "nestr" => NonEmptyStr,
+      "email" => Email,
+      "url" => Url,
+      "uuid" => Uuid,
+      "ipv4" => Ipv4,
+      "ipv6" => Ipv6,
+      "date" => Date,
+      "datetime" => DateTime,
+      "enum" => Enum,
+      _ if self.types.contains_key(val) => Custom(self.types[val].name),
       _ => {
-        let p_vals = [
-          "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64",
-          "str", "bool",
-        ];
+        let p_vals = self.data_types();
         let mut extra_help = String::new();
         if let Some(matches) = parser::did_you_mean(val, p_vals.iter()) {
           extra_help = format!("did you mean: `{}` ?", matches);
@@ -440,44 +1410,38 @@ impl<'s> Sigma<'s> {
     pairs: Pairs<'f, Rule>,
     mut var: Variable<'f>,
   ) -> SigmaResult<'f, Variable<'f>> {
+    // the type of the value at this point of the pipeline.
+    let mut current_type = var.data_type.as_ref().map(|t| t.0.clone());
     for pair in pairs {
       let rule = pair.as_rule();
       match rule {
         Rule::function => {
-          if var.data_type.is_none() || !var.typed {
-            return Err(PestError::new_from_span(
-              ErrorVariant::ParsingError {
-                positives: vec![Rule::data_type],
-                negatives: vec![],
-              },
-              var.name_span.unwrap(),
-            ));
-          }
-          let mut function = pair.into_inner();
-          let _sep = function.next().unwrap();
-          let function_name = function.next().unwrap();
-          if !self.functions.contains_key(function_name.as_str()) {
-            let mut extra_help = String::new();
-            if let Some(matches) = parser::did_you_mean(
-              function_name.as_str(),
-              self.functions.keys(),
-            ) {
-              extra_help = format!("did you mean: `{}` ?", matches);
-            }
-            return Err(PestError::new_from_span(
-              ErrorVariant::CustomError {
-                message: format!(
-                  "undefined function: {} {}",
-                  function_name.as_str(),
-                  extra_help
-                ),
-              },
-              function_name.as_span(),
-            ));
-          }
-          var
-            .functions
-            .push((function_name.as_str(), function_name.as_span()));
+          let input_type = match current_type {
+            Some(current_type) => current_type,
+            // untyped variable, infer its type from the first function.
+            None => {
+              let function_name = pair.clone().into_inner().nth(1).unwrap();
+              let f = self.function(&function_name)?;
+              if f.input == DataType::Enum {
+                return Err(PestError::new_from_span(
+                  ErrorVariant::CustomError {
+                    message: format!(
+                      "cannot infer the data type of `{}` from function {}, \
+                       consider adding a data type for it",
+                      var.name,
+                      function_name.as_str()
+                    ),
+                  },
+                  var.name_span.unwrap(),
+                ));
+              }
+              var.data_type = Some((f.input.clone(), function_name.as_span()));
+              f.input.clone()
+            },
+          };
+          let (call, output) = self.parse_function_call(pair, &input_type)?;
+          current_type = Some(output);
+          var.functions.push(call);
         },
         Rule::pair_close => {
           var.location = (0, pair.as_span().end());
@@ -489,71 +1453,262 @@ impl<'s> Sigma<'s> {
     Ok(var)
   }
 
-  #[inline]
-  fn validate_data_type(
+  /// the helper function registered with that name, or the built-in one with
+  /// that flat name.
+  fn resolve(&self, name: &str) -> Option<&Function> {
+    self
+      .functions
+      .get(name)
+      .or_else(|| self.functions.get(self.aliases.get(name)?))
+  }
+
+  /// the helper function with that name.
+  fn function<'f>(
     &self,
-    var: &Variable,
-    data: &str,
-  ) -> SigmaResult<'s, ()> {
-    if let Some(data_type) = &var.data_type {
-      use self::DataType::*;
-      let data_type_error = {
-        let extra = if data.len() > 15 { "..." } else { "" };
-        PestError::<Rule>::new_from_span(
+    function_name: &Pair<'f, Rule>,
+  ) -> SigmaResult<'f, &Function> {
+    match self.resolve(function_name.as_str()) {
+      Some(f) => Ok(f),
+      None => {
+        let mut extra_help = String::new();
+        let names = self.functions.keys().chain(self.aliases.keys());
+        if let Some(matches) =
+          parser::did_you_mean(function_name.as_str(), names)
+        {
+          extra_help = format!("did you mean: `{}` ?", matches);
+        }
+        Err(PestError::new_from_span(
           ErrorVariant::CustomError {
             message: format!(
-              "cannot parse input `{}{}` into `{:?}` for var `{}` !",
-              data.chars().take(15).collect::<String>(),
-              extra,
-              data_type.0,
-              var.name
+              "undefined function: {} {}",
+              function_name.as_str(),
+              extra_help
             ),
           },
-          data_type.1.clone(),
-        )
-      };
-      match data_type.0 {
-        U8 => {
-          data.parse::<u8>().map_err(|_| data_type_error)?;
-        },
-        I8 => {
-          data.parse::<i8>().map_err(|_| data_type_error)?;
-        },
-        U16 => {
-          data.parse::<u16>().map_err(|_| data_type_error)?;
-        },
-        I16 => {
-          data.parse::<i16>().map_err(|_| data_type_error)?;
-        },
-        U32 => {
-          data.parse::<u32>().map_err(|_| data_type_error)?;
-        },
-        I32 => {
-          data.parse::<i32>().map_err(|_| data_type_error)?;
-        },
-        U64 => {
-          data.parse::<u64>().map_err(|_| data_type_error)?;
-        },
-        I64 => {
-          data.parse::<i64>().map_err(|_| data_type_error)?;
-        },
-        F32 => {
-          data.parse::<f32>().map_err(|_| data_type_error)?;
-        },
-        F64 => {
-          data.parse::<f64>().map_err(|_| data_type_error)?;
+          function_name.as_span(),
+        ))
+      },
+    }
+  }
+
+  /// type check a `function` pair against the data type of its input,
+  /// returns the call and the data type of its output.
+  fn parse_function_call<'f>(
+    &self,
+    pair: Pair<'f, Rule>,
+    input_type: &DataType,
+  ) -> SigmaResult<'f, (FunctionCall<'f>, DataType)> {
+    let mut function = pair.into_inner();
+    let _sep = function.next().unwrap();
+    let function_name = function.next().unwrap();
+    let f = self.function(&function_name)?;
+    if !input_type.is_assignable_to(&f.input) {
+      return Err(PestError::new_from_span(
+        ErrorVariant::CustomError {
+          message: format!(
+            "mismatched types: function {} expects `{}` but got `{}`",
+            function_name.as_str(),
+            f.input,
+            input_type
+          ),
         },
-        Bool => {
-          data.parse::<bool>().map_err(|_| data_type_error)?;
+        function_name.as_span(),
+      ));
+    }
+    let args = self.parse_arguments(f, &function_name, function)?;
+    let call = FunctionCall {
+      name: function_name.as_str(),
+      args,
+      span: function_name.as_span(),
+    };
+    Ok((call, f.output.clone()))
+  }
+
+  /// parse the function call arguments and validate them against the
+  /// function params.
+  fn parse_arguments<'f>(
+    &self,
+    f: &Function,
+    function_name: &Pair<'f, Rule>,
+    rest: Pairs<'f, Rule>,
+  ) -> SigmaResult<'f, Vec<String>> {
+    let mut args = Vec::new();
+    let mut spans = Vec::new();
+    if let Some(arguments) = rest.peek() {
+      for arg in arguments.into_inner() {
+        let arg = arg.into_inner().next().unwrap();
+        spans.push((arg.as_span(), arg.as_rule()));
+        args.push(match arg.as_rule() {
+          Rule::string => parser::unescape(arg.as_str()),
+          _ => arg.as_str().to_owned(),
+        });
+      }
+    }
+    if args.len() < f.required || args.len() > f.params.len() {
+      let expected = if f.required == f.params.len() {
+        f.required.to_string()
+      } else {
+        format!("{} to {}", f.required, f.params.len())
+      };
+      return Err(PestError::new_from_span(
+        ErrorVariant::CustomError {
+          message: format!(
+            "function {} takes {} argument(s) but {} were given",
+            f.name,
+            expected,
+            args.len()
+          ),
         },
-        _ => {
-          // it must be a string then
+        function_name.as_span(),
+      ));
+    }
+    for ((arg, (span, rule)), param) in args.iter().zip(spans).zip(&f.params) {
+      let quoted = rule == Rule::string;
+      if quoted == param.is_numeric() || !self.is_valid(param, arg) {
+        return Err(PestError::new_from_span(
+          ErrorVariant::CustomError {
+            message: format!(
+              "mismatched types: function {} expects `{}` argument",
+              f.name, param
+            ),
+          },
+          span,
+        ));
+      }
+    }
+    if let Some(check) = f.check {
+      check(&args).map_err(|message| {
+        PestError::new_from_span(
+          ErrorVariant::CustomError { message },
+          function_name.as_span(),
+        )
+      })?;
+    }
+    Ok(args)
+  }
+
+  #[inline]
+  fn validate_data_type(
+    &self,
+    var: &Variable,
+    data: &str,
+  ) -> SigmaResult<'s, ()> {
+    if let Some(data_type) = &var.data_type {
+      let data_type_error = PestError::<Rule>::new_from_span(
+        ErrorVariant::CustomError {
+          message: format!(
+            "cannot parse input `{}` into `{}` for var `{}` !",
+            preview(data),
+            data_type.0,
+            var.name
+          ),
         },
-      };
-      return Ok(());
+        data_type.1,
+      );
+      if !self.is_valid(&data_type.0, data)
+        || !self.fits_functions(&var.functions, data)
+      {
+        return Err(data_type_error);
+      }
+      if let Some((constraint, span)) = &var.constraint {
+        constraint::check_constraint(&data_type.0, constraint, data).map_err(
+          |reason| {
+            PestError::new_from_span(
+              ErrorVariant::CustomError {
+                message: format!(
+                  "input `{}` for var `{}` {}",
+                  preview(data),
+                  var.name,
+                  reason
+                ),
+              },
+              *span,
+            )
+          },
+        )?;
+      }
     }
     Ok(())
   }
+
+  /// is the input a valid value of that data type.
+  fn is_valid(&self, data_type: &DataType, data: &str) -> bool {
+    use self::DataType::*;
+    match data_type {
+      U8 => data.parse::<u8>().is_ok(),
+      I8 => data.parse::<i8>().is_ok(),
+      U16 => data.parse::<u16>().is_ok(),
+      I16 => data.parse::<i16>().is_ok(),
+      U32 => data.parse::<u32>().is_ok(),
+      I32 => data.parse::<i32>().is_ok(),
+      U64 => data.parse::<u64>().is_ok(),
+      I64 => data.parse::<i64>().is_ok(),
+      U128 => data.parse::<u128>().is_ok(),
+      I128 => data.parse::<i128>().is_ok(),
+      Usize => data.parse::<usize>().is_ok(),
+      Isize => data.parse::<isize>().is_ok(),
+      F32 => data.parse::<f32>().is_ok(),
+      F64 => data.parse::<f64>().is_ok(),
+      Bool => data.parse::<bool>().is_ok(),
+      Char => data.parse::<char>().is_ok(),
+      NonEmptyStr => !data.is_empty(),
+      Email => {
+        static EMAIL: OnceLock<Regex> = OnceLock::new();
+        let email = EMAIL
+          .get_or_init(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s.]+$").unwrap());
+        email.is_match(data)
+      },
+      Url => url::Url::parse(data).is_ok(),
+      Uuid => {
+        static UUID: OnceLock<Regex> = OnceLock::new();
+        let uuid = UUID.get_or_init(|| {
+          Regex::new(
+            r"^(?i)[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$",
+          )
+          .unwrap()
+        });
+        uuid.is_match(data)
+      },
+      Ipv4 => data.parse::<Ipv4Addr>().is_ok(),
+      Ipv6 => data.parse::<Ipv6Addr>().is_ok(),
+      Date => NaiveDate::parse_from_str(data, "%Y-%m-%d").is_ok(),
+      DateTime => chrono::DateTime::parse_from_rfc3339(data).is_ok(),
+      Custom(name) => (self.types[name].validate)(data),
+      // the enum variants are checked by its constraint.
+      Str | Enum => true,
+    }
+  }
+}
+
+/// the name of the variable in a `var_pair`.
+fn var_pair_name<'a>(pair: &Pair<'a, Rule>) -> &'a str {
+  let var = pair.clone().into_inner().nth(1).unwrap();
+  var.into_inner().next().unwrap().as_str()
+}
+
+/// the first few chars of the input, used in the error messages.
+fn preview(data: &str) -> String {
+  let extra = if data.len() > 15 { "..." } else { "" };
+  format!("{}{}", data.chars().take(15).collect::<String>(), extra)
+}
+
+fn unbinded_error<'a, I, T>(var: &Variable<'a>, binded: I) -> PestError<Rule>
+where
+  I: IntoIterator<Item = &'a T>,
+  T: AsRef<str> + ?Sized + 'a,
+{
+  let extra_help;
+  if let Some(matches) = parser::did_you_mean(var.name, binded) {
+    extra_help = format!("did you mean: `{}` ?", matches);
+  } else {
+    extra_help = "consider adding a bind for it".to_owned();
+  }
+  PestError::new_from_span(
+    ErrorVariant::CustomError {
+      message: format!("unbinded variable: `{}` {}", var.name, extra_help),
+    },
+    var.name_span.unwrap(),
+  )
 }
 
 impl<'s> From<&'s str> for Sigma<'s> {
@@ -609,6 +1764,490 @@ mod tests {
     println!("{:?}", output);
   }
 
+  #[test]
+  fn introspection() {
+    let input =
+      "{{ b?: u8 }} {{ a: str | TRIM | UPPERCASE }} {{ c?: str | TRIM }}";
+    let sigma = Sigma::new(input).bind("a", "x").parse().unwrap();
+    let names: Vec<_> = sigma.variables().iter().map(|v| v.name).collect();
+    assert_eq!(names, vec!["b", "a", "c"]);
+    let a = sigma.variables()[1];
+    assert_eq!(a.location, (13, 44));
+    assert_eq!(&input[a.location.0..a.location.1], a.pair_str);
+    let helpers: Vec<_> = sigma.helpers().into_iter().collect();
+    assert_eq!(helpers, vec!["TRIM", "UPPERCASE"]);
+  }
+
+  #[test]
+  fn same_variable_in_many_placeholders() {
+    let input = "{{ name }}, {{ name | UPPERCASE }} {{ name ~ \"!\" }}";
+    let sigma = Sigma::new(input).bind("name", "sigma").parse().unwrap();
+    assert_eq!(sigma.variables().len(), 1);
+    assert_eq!("sigma, SIGMA sigma!", sigma.clone().compile().unwrap());
+    let errors = sigma.check(&HashMap::new()).unwrap_err();
+    assert_eq!(errors.len(), 1);
+  }
+
+  #[test]
+  fn check_reports_every_error() {
+    let sigma = Sigma::new("{{ username: str }} {{ id: u8 }} {{ bio? }}")
+      .bind("username", "someone")
+      .bind("id", "1")
+      .parse()
+      .unwrap();
+    let mut bindings = HashMap::new();
+    assert!(sigma.check(&bindings).is_err());
+    bindings.insert("usrname", "someone");
+    bindings.insert("id", "-1");
+    let errors = sigma.check(&bindings).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].to_string().contains("did you mean: `usrname`"));
+    assert!(errors[1].to_string().contains("cannot parse input `-1`"));
+    bindings.insert("username", "someone");
+    bindings.insert("id", "1");
+    assert_eq!(sigma.check(&bindings), Ok(()));
+  }
+
+  #[test]
+  fn parse_all_reports_every_error() {
+    let input = "{{ id: u9 }} {{ name: str | TRIMM }} {{ name }}";
+    let errors = Sigma::new(input).parse_all().unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].to_string().contains("u9"));
+    assert!(errors[1].to_string().contains("did you mean: `TRIM`"));
+    // the pass stops at a broken `let`, the last tag is not reported.
+    let input = "{{ a + 1 }} {{ b * \"x\" }} {{# let c = d }} {{ c + 1.0.0 }}";
+    let errors = Sigma::new(input).parse_all().unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert!(errors[1].to_string().contains("`*` expects numbers"));
+    assert!(errors[2].to_string().contains("data type of `d`"));
+    let first = Sigma::new(input).parse().unwrap_err();
+    assert_eq!(errors[0].to_string(), first.to_string());
+    assert!(Sigma::new("{{ name }}").parse_all().is_ok());
+  }
+
+  #[test]
+  fn unbinded_at_render_time() {
+    let sigma = Sigma::new("Hi {{ username: str }}{{ bang? }}")
+      .parse()
+      .unwrap();
+    let err = sigma.render(&HashMap::new()).unwrap_err();
+    assert!(err.to_string().contains("unbinded variable: `username`"));
+    let mut bindings = HashMap::new();
+    bindings.insert("user", "someone");
+    let err = sigma.render(&bindings).unwrap_err();
+    assert!(err.to_string().contains("did you mean: `user`"));
+    let empty = RenderOptions::new().unbinded(Unbinded::Empty);
+    assert_eq!("Hi ", sigma.render_with(&bindings, &empty).unwrap());
+    let verbatim = RenderOptions::new().unbinded(Unbinded::Verbatim);
+    assert_eq!(
+      "Hi {{ username: str }}",
+      sigma.render_with(&bindings, &verbatim).unwrap()
+    );
+    bindings.insert("username", "someone");
+    assert_eq!("Hi someone", sigma.render(&bindings).unwrap());
+  }
+
+  #[test]
+  fn strict_rejects_unused_bindings() {
+    let sigma = Sigma::new("{{ username?: str }}").strict().parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("usrname", "someone");
+    bindings.insert("zzz", "x");
+    let errors = sigma.check(&bindings).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].to_string().contains("did you mean: `username` ?"));
+    assert!(errors[1].to_string().ends_with("unused binding: `zzz`"));
+    assert!(sigma.render(&bindings).is_err());
+    let options = RenderOptions::new();
+    assert_eq!("", sigma.render_with(&bindings, &options).unwrap());
+    bindings.clear();
+    bindings.insert("username", "someone");
+    assert_eq!("someone", sigma.render(&bindings).unwrap());
+  }
+
+  #[test]
+  fn more_data_types() {
+    let input = "{{ id: u128 }} {{ initial: char }} {{ name: nestr }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("id", "340282366920938463463374607431768211455");
+    bindings.insert("initial", "é");
+    bindings.insert("name", "sigma");
+    assert!(sigma.check(&bindings).is_ok());
+    bindings.insert("id", "-1");
+    bindings.insert("initial", "ab");
+    bindings.insert("name", "");
+    assert_eq!(sigma.check(&bindings).unwrap_err().len(), 3);
+    let sigma = Sigma::new("{{ id: i128 }} {{ len: usize }} {{ off: isize }}")
+      .parse()
+      .unwrap();
+    let types: Vec<_> = sigma
+      .variables()
+      .iter()
+      .map(|v| v.data_type.clone().unwrap().0)
+      .collect();
+    assert_eq!(
+      types,
+      vec![DataType::I128, DataType::Usize, DataType::Isize]
+    );
+  }
+
+  #[test]
+  fn semantic_data_types() {
+    let input = "{{ a: email }} {{ b: url }} {{ c: uuid }} {{ d: ipv4 }} \
+                 {{ e: ipv6 }} {{ f: date }} {{ g: datetime }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("a", "someone@example.com");
+    bindings.insert("b", "https://example.com/a?b=c");
+    bindings.insert("c", "67e55044-10b1-426f-9247-bb680e5fe0c8");
+    bindings.insert("d", "127.0.0.1");
+    bindings.insert("e", "::1");
+    bindings.insert("f", "2019-02-24");
+    bindings.insert("g", "2019-02-24T10:30:00+02:00");
+    assert_eq!(sigma.check(&bindings), Ok(()));
+    let mut bad = HashMap::new();
+    bad.insert("a", "someone@");
+    bad.insert("b", "example.com");
+    bad.insert("c", "67e55044-10b1-426f-9247");
+    bad.insert("d", "256.0.0.1");
+    bad.insert("e", "127.0.0.1");
+    bad.insert("f", "2019-02-30");
+    bad.insert("g", "2019-02-24 10:30");
+    let errors = sigma.check(&bad).unwrap_err();
+    assert_eq!(errors.len(), 7);
+    assert!(errors[0]
+      .to_string()
+      .contains("cannot parse input `someone@` into `Email` for var `a`"));
+  }
+
+  #[test]
+  fn custom_data_types() {
+    let input = "{{ code: currency_code }} {{ price: f64 }}";
+    let sigma = Sigma::new(input)
+      .register_type("currency_code", |input| {
+        input.len() == 3 && input.chars().all(|c| c.is_ascii_uppercase())
+      })
+      .parse()
+      .unwrap();
+    assert_eq!(
+      sigma.variables()[0].data_type.as_ref().unwrap().0,
+      DataType::Custom("currency_code")
+    );
+    let mut bindings = HashMap::new();
+    bindings.insert("price", "9.99");
+    bindings.insert("code", "USD");
+    assert_eq!("USD 9.99", sigma.render(&bindings).unwrap());
+    bindings.insert("code", "usd");
+    let err = sigma.render(&bindings).unwrap_err().to_string();
+    assert!(err.contains("cannot parse input `usd` into `currency_code`"));
+
+    let err = Sigma::new("{{ code: currency_cod }}")
+      .register_type("currency_code", |_| true)
+      .parse()
+      .unwrap_err();
+    assert!(err.to_string().contains("did you mean: `currency_code` ?"));
+  }
+
+  #[test]
+  #[should_panic(expected = "cannot shadow the built-in one")]
+  fn custom_data_type_shadowing_a_built_in() {
+    let _ = Sigma::new("{{ x: str }}").register_type("str", |v| v == "ok");
+  }
+
+  #[test]
+  #[should_panic(expected = "must be in lowercase")]
+  fn custom_data_type_not_in_lowercase() {
+    let _ = Sigma::new("{{ x: sku }}").register_type("SKU", |_| true);
+  }
+
+  #[test]
+  fn constraints() {
+    let input = "{{ age: u8(18..=120) }} {{ name: str(len 1..=5) }} \
+                 {{ code: str(/^[A-Z]{3}$/) }} {{ tier: enum(gold, silver) }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    assert_eq!(
+      sigma.variables()[3].constraint.as_ref().unwrap().0,
+      Constraint::OneOf(vec!["gold".to_owned(), "silver".to_owned()])
+    );
+    let mut bindings = HashMap::new();
+    bindings.insert("age", "18");
+    bindings.insert("name", "Σίγμα");
+    bindings.insert("code", "USD");
+    bindings.insert("tier", "gold");
+    assert_eq!(sigma.check(&bindings), Ok(()));
+    bindings.insert("age", "17");
+    bindings.insert("name", "");
+    bindings.insert("code", "usd");
+    bindings.insert("tier", "silvr");
+    let errors: Vec<_> = sigma
+      .check(&bindings)
+      .unwrap_err()
+      .iter()
+      .map(|e| e.to_string())
+      .collect();
+    assert!(errors[0].contains("input `17` for var `age` is out of range"));
+    assert!(errors[1].contains("has a length out of range `len 1..=5`"));
+    assert!(errors[2].contains("does not match `/^[A-Z]{3}$/`"));
+    assert!(errors[3].contains("is not one of `gold, silver` did you mean"));
+  }
+
+  #[test]
+  fn malformed_constraints() {
+    let errors = [
+      ("{{ a: u8(1..=300) }}", "invalid range `1..=300` for `U8`"),
+      ("{{ a: u8(5..1) }}", "invalid range `5..1`"),
+      ("{{ a: u8(..0) }}", "invalid range `..0`"),
+      ("{{ a: i8(..-128) }}", "invalid range `..-128`"),
+      ("{{ a: str(len ..0) }}", "invalid length range `..0`"),
+      ("{{ a: str(1..5) }}", "range constraint is only for numbers"),
+      (
+        "{{ a: u8(len 1..) }}",
+        "length constraint is only for strings",
+      ),
+      ("{{ a: str(/[a-/) }}", "invalid pattern `/[a-/`"),
+      ("{{ a: str(a, b) }}", "variants are only for `enum`"),
+      ("{{ a: enum(a, a) }}", "duplicated variant `a`"),
+      ("{{ a: enum }}", "missing enum variants"),
+    ];
+    for (input, message) in &errors {
+      let err = Sigma::new(input).parse().unwrap_err().to_string();
+      assert!(err.contains(message), "{}", err);
+    }
+    // every data type written as a string has a length and a pattern.
+    let input = "{{ id: uuid(/^0/) }} {{ ip: ipv4(len ..=15) }} \
+                 {{ on: date(/-01$/) }} {{ c: char(/[a-z]/) }}";
+    assert!(Sigma::new(input).parse().is_ok());
+  }
+
+  #[test]
+  fn typed_pipeline() {
+    let err = Sigma::new("{{ id: u32 | UPPERCASE }}").parse().unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("function UPPERCASE expects `Str` but got `U32`"));
+    let sigma = Sigma::new("{{ id: u32 | ZERO_PAD | TRIM }}")
+      .register_typed_fn("ZERO_PAD", DataType::U32, DataType::Str, |input| {
+        format!("{:0>4}", input)
+      })
+      .parse()
+      .unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("id", "42");
+    assert_eq!("0042", sigma.render(&bindings).unwrap());
+    let err = Sigma::new("{{ id: str | ZERO_PAD }}")
+      .register_typed_fn("ZERO_PAD", DataType::U32, DataType::Str, |i| i)
+      .parse()
+      .unwrap_err();
+    assert!(err.to_string().contains("expects `U32` but got `Str`"));
+  }
+
+  #[test]
+  fn untyped_pipeline() {
+    let sigma = Sigma::new("{{ name | TRIM | UPPERCASE }} {{ n | DOUBLE }}")
+      .register_typed_fn("DOUBLE", DataType::U32, DataType::U32, |input| {
+        (input.parse::<u32>().unwrap() * 2).to_string()
+      })
+      .parse()
+      .unwrap();
+    let vars = sigma.variables();
+    assert!(!vars[1].typed);
+    assert_eq!(vars[1].data_type.as_ref().unwrap().0, DataType::U32);
+    let mut bindings = HashMap::new();
+    bindings.insert("name", " someone ");
+    bindings.insert("n", "21");
+    assert_eq!("SOMEONE 42", sigma.render(&bindings).unwrap());
+    bindings.insert("n", "x");
+    let err = sigma.render(&bindings).unwrap_err().to_string();
+    assert!(err.contains("cannot parse input `x` into `U32` for var `n`"));
+
+    let err = Sigma::new("{{ tier | TIER }}")
+      .register_typed_fn("TIER", DataType::Enum, DataType::Str, |i| i)
+      .parse()
+      .unwrap_err();
+    assert!(err
+      .to_string()
+      .contains("cannot infer the data type of `tier`"));
+  }
+
+  #[test]
+  fn number_helpers() {
+    let input =
+      "{{ price: f64 | CURRENCY(\"USD\") }} x {{ qty: u32 | THOUSANDS }} \
+                 ({{ ratio | PERCENT(1) }}) {{ size: u64 | FILESIZE }} \
+                 {{ pi: f32 | FORMAT_NUMBER(2) | THOUSANDS(\" \") }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("price", "1999.5");
+    bindings.insert("qty", "12000");
+    bindings.insert("ratio", "0.256");
+    bindings.insert("size", "5242880");
+    bindings.insert("pi", "3.14159");
+    assert_eq!(
+      "$1,999.50 x 12,000 (25.6%) 5 MB 3.14",
+      sigma.render(&bindings).unwrap()
+    );
+  }
+
+  #[test]
+  fn string_helpers() {
+    let input =
+      "/{{ title | SLUG }} {{ name: nestr | KEBAB_CASE | TRUNCATE(5) }} \
+                 {{ heading | LENGTH | FORMAT_NUMBER(1) }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("title", "Ünïcode Strings");
+    bindings.insert("heading", "Ünïcode Strings");
+    bindings.insert("name", "SigmaTemplates");
+    assert_eq!(
+      "/unicode-strings sigma… 15.0",
+      sigma.render(&bindings).unwrap()
+    );
+    for input in
+      ["{{ name | WORDWRAP(0) }}", "{{ name | WORDWRAP(00) }}"].iter()
+    {
+      let err = Sigma::new(input).parse().unwrap_err();
+      assert!(err.to_string().contains("cannot wrap to zero width"));
+    }
+    let input = "{{ name | INDENT(18446744073709551615) }}";
+    let err = Sigma::new(input).parse().unwrap_err();
+    assert!(err.to_string().contains("cannot indent by more than 1024"));
+  }
+
+  #[test]
+  fn helper_modules() {
+    struct Acme;
+    impl HelperModule for Acme {
+      fn name(&self) -> &str {
+        "acme"
+      }
+
+      fn functions(&self) -> Vec<Function> {
+        vec![Function::new(
+          "WRAP",
+          DataType::Str,
+          DataType::Str,
+          |s, _| format!("[{}]", s),
+        )]
+      }
+    }
+    let input =
+      "{{ name | STR::TRIM | ACME::WRAP }} {{ n: f64 | NUM::ROUND(1) }}";
+    let sigma = Sigma::new(input).import(Acme).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("name", " sigma ");
+    bindings.insert("n", "2.46");
+    assert_eq!("[sigma] 2.5", sigma.render(&bindings).unwrap());
+    assert!(sigma.helpers().contains("ACME::WRAP"));
+    let err = Sigma::new("{{ name | STR::TRIN }}").parse().unwrap_err();
+    assert!(err.to_string().contains("did you mean: `STR::TRIM`"));
+    assert!(Sigma::new("{{ name | WRAP }}")
+      .import(Acme)
+      .parse()
+      .is_err());
+  }
+
+  #[test]
+  fn date_helpers() {
+    let input = "{{ at: datetime | TIMEZONE(\"Africa/Cairo\") | \
+                 DATE_FORMAT(\"%H:%M\") }}, {{ sent | RELATIVE_TIME }}, \
+                 {{ born: date | DATE_FORMAT(\"%Y\") }}, \
+                 {{ ts: i64 | DATE_FORMAT(\"%F\") }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("at", "2019-02-24T10:00:00Z");
+    bindings.insert("sent", "2019-02-24T10:00:00Z");
+    bindings.insert("born", "1990-05-01");
+    bindings.insert("ts", "1550966400");
+    let now = "2019-02-24T10:05:00Z".parse().unwrap();
+    let options = RenderOptions::new().now(now);
+    assert_eq!(
+      "12:00, 5 minutes ago, 1990, 2019-02-24",
+      sigma.render_with(&bindings, &options).unwrap()
+    );
+    // a timestamp out of the `datetime` range is not a made-up date.
+    bindings.insert("ts", "9223372036854775807");
+    let err = sigma.render_with(&bindings, &options).unwrap_err();
+    assert!(err.to_string().contains("cannot parse input"), "{}", err);
+    let sigma = Sigma::new("{{ ts * 2: i64 | DATE_FORMAT(\"%Y\") }}")
+      .parse()
+      .unwrap();
+    bindings.insert("ts", "4611686018427387903");
+    let err = sigma.render(&bindings).unwrap_err();
+    assert!(err.to_string().contains("into `DateTime`"), "{}", err);
+    // the output of a date helper could be past the year 9999.
+    let input = "{{ ts: i64 | UTC | DATE_FORMAT(\"%Y\") }}\
+                 {{ at: i64 | TIMEZONE(\"+01:00\") | DATE_FORMAT(\"%Y\") }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    for ts in ["300000000000", "253402300799"].iter() {
+      bindings.insert("ts", ts);
+      bindings.insert("at", ts);
+      let err = sigma.render(&bindings).unwrap_err().to_string();
+      assert!(err.contains("for helper `DATE_FORMAT`"), "{}", err);
+    }
+    let errors = [
+      (
+        "{{ a: datetime | DATE_FORMAT(\"%Q\") }}",
+        "invalid date format `%Q`",
+      ),
+      (
+        "{{ a: datetime | TIMEZONE(\"Cairo\") }}",
+        "unknown time zone `Cairo`",
+      ),
+      (
+        "{{ a: u64 | DATE_FORMAT(\"%F\") }}",
+        "expects `DateTime` but got `U64`",
+      ),
+    ];
+    for (input, message) in &errors {
+      let err = Sigma::new(input).parse().unwrap_err().to_string();
+      assert!(err.contains(message), "{}", err);
+    }
+  }
+
+  #[test]
+  fn function_arguments_are_checked() {
+    let errors = [
+      (
+        "{{ a: f64 | ROUND(1, 2) }}",
+        "takes 0 to 1 argument(s) but 2",
+      ),
+      ("{{ a: f64 | FORMAT_NUMBER }}", "takes 1 argument(s) but 0"),
+      ("{{ a: f64 | ROUND(\"2\") }}", "expects `U8` argument"),
+      ("{{ a: f64 | ROUND(300) }}", "expects `U8` argument"),
+      ("{{ a: f64 | THOUSANDS(1) }}", "expects `Str` argument"),
+      ("{{ a: bool | ROUND }}", "expects `F64` but got `Bool`"),
+    ];
+    for (input, message) in &errors {
+      let err = Sigma::new(input).parse().unwrap_err().to_string();
+      assert!(err.contains(message), "{}", err);
+    }
+  }
+
+  #[test]
+  fn validate_before_pipeline() {
+    let sigma = Sigma::new("{{ code: str(/^[a-z]+$/) | UPPERCASE }}")
+      .parse()
+      .unwrap();
+    let mut bindings = HashMap::new();
+    bindings.insert("code", "abc");
+    assert_eq!("ABC", sigma.render(&bindings).unwrap());
+    bindings.insert("code", "ABC");
+    assert!(sigma.render(&bindings).is_err());
+    let sigma = Sigma::new("{{ email: email | UPPERCASE }}")
+      .parse()
+      .unwrap();
+    bindings.insert("email", "someone@example.com");
+    assert_eq!("SOMEONE@EXAMPLE.COM", sigma.render(&bindings).unwrap());
+  }
+
+  #[test]
+  fn data_type_prefix_is_not_a_data_type() {
+    let err = Sigma::new("{{ name: string }}").parse().unwrap_err();
+    assert!(err.to_string().contains("unknown data type: `string`"));
+  }
+
   #[test]
   fn test_sigma_macro() {
     let username = "someone";
@@ -0,0 +1,305 @@
+use crate::{
+  expr::{error, Expr, Halt, Local, Locals, Placeholder, Value},
+  parser::{self, Rule},
+  preview, unbinded_error, DataType, Node, RenderOptions, Scope, Sigma,
+  SigmaResult, Unbinded,
+};
+use pest::{iterators::Pair, Span};
+use std::collections::HashMap;
+
+/// A `{{# macro name(params) }} ... {{/ macro }}` definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Macro<'a> {
+  pub(crate) name: &'a str,
+  /// where the name is in the definition.
+  pub(crate) name_span: Span<'a>,
+  /// the parameters are the local variables of the body.
+  pub(crate) params: Vec<Local<'a>>,
+  pub(crate) body: Vec<Node<'a>>,
+}
+
+/// A `{{> name(args) }}` call of a macro.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Call<'a> {
+  /// the index of the macro in `Sigma::macros`.
+  index: usize,
+  args: Vec<Expr<'a>>,
+  /// the raw `{{> ... }}` pair as written in the template
+  pair_str: &'a str,
+}
+
+impl<'s> Sigma<'s> {
+  /// parse the name and the parameters of a `macro_open` pair, the body is
+  /// parsed later by `parse_macro_body`, an untyped parameter is a `str`.
+  pub(crate) fn parse_macro_open(
+    &mut self,
+    pair: Pair<'s, Rule>,
+  ) -> SigmaResult<'s, Macro<'s>> {
+    let mut inner = pair.into_inner();
+    let _open = inner.next().unwrap();
+    let name = inner.next().unwrap();
+    if self.macros.iter().any(|other| other.name == name.as_str()) {
+      return Err(error(
+        format!("macro `{}` is already defined", name.as_str()),
+        name.as_span(),
+      ));
+    }
+    let mut params: Vec<Local> = Vec::new();
+    for param in inner.next().unwrap().into_inner() {
+      let mut parts = param.into_inner();
+      let param_name = parts.next().unwrap();
+      if params.iter().any(|other| other.name == param_name.as_str()) {
+        return Err(error(
+          format!("duplicate parameter `{}`", param_name.as_str()),
+          param_name.as_span(),
+        ));
+      }
+      let data_type = match parts.nth(1) {
+        Some(data_type) => self.parse_data_type(&data_type)?,
+        None => DataType::Str,
+      };
+      self.locals += 1;
+      params.push(Local {
+        name: param_name.as_str(),
+        slot: self.locals - 1,
+        data_type,
+      });
+    }
+    Ok(Macro {
+      name: name.as_str(),
+      name_span: name.as_span(),
+      params,
+      body: Vec::new(),
+    })
+  }
+
+  /// parse the body of the next macro in `scope`, the body sees only its
+  /// parameters and its own local variables.
+  pub(crate) fn parse_macro_body(
+    &mut self,
+    pair: Pair<'s, Rule>,
+    scope: &mut Scope<'s>,
+  ) -> SigmaResult<'s, ()> {
+    let index = scope.macros.next().unwrap();
+    let body = pair.into_inner().nth(1).unwrap();
+    let span = body.as_span();
+    let params = self.macros[index].params.clone();
+    let outer = std::mem::replace(&mut scope.locals, params);
+    scope.caller = Some(index);
+    let nodes = self.parse_nodes(
+      body.into_inner().collect(),
+      span.start()..span.end(),
+      scope,
+    );
+    scope.locals = outer;
+    scope.caller = None;
+    self.macros[index].body = nodes?;
+    Ok(())
+  }
+
+  /// type check a `call_pair` against the macro parameters, a macro can only
+  /// call the macros defined before it, so there is no recursion.
+  pub(crate) fn parse_call_pair(
+    &mut self,
+    pair: Pair<'s, Rule>,
+    scope: &mut Scope<'s>,
+  ) -> SigmaResult<'s, Call<'s>> {
+    let mut cx = Placeholder::new(&pair, &mut scope.declared, &scope.locals);
+    let pair_str = pair.as_str();
+    let mut inner = pair.into_inner();
+    let _open = inner.next().unwrap();
+    let name = inner.next().unwrap();
+    let args: Vec<_> = inner.next().unwrap().into_inner().collect();
+    let index = match self.macros.iter().position(|m| m.name == name.as_str()) {
+      Some(index) => index,
+      None => {
+        let names = self.macros.iter().map(|m| m.name);
+        let extra_help = parser::did_you_mean(name.as_str(), names)
+          .map(|matches| format!(" did you mean: `{}` ?", matches))
+          .unwrap_or_default();
+        return Err(error(
+          format!("undefined macro: `{}`{}", name.as_str(), extra_help),
+          name.as_span(),
+        ));
+      },
+    };
+    if let Some(caller) = scope.caller.filter(|caller| index >= *caller) {
+      return Err(error(
+        format!(
+          "macro `{}` cannot call `{}`, a macro can only call the macros \
+           defined before it",
+          self.macros[caller].name,
+          name.as_str()
+        ),
+        name.as_span(),
+      ));
+    }
+    let params = self.macros[index].params.clone();
+    if args.len() != params.len() {
+      return Err(error(
+        format!(
+          "macro `{}` takes {} argument(s) but {} were given",
+          name.as_str(),
+          params.len(),
+          args.len()
+        ),
+        name.as_span(),
+      ));
+    }
+    let mut checked = Vec::new();
+    for (arg, param) in args.into_iter().zip(&params) {
+      let arg = self.check_expr(arg, Some(&param.data_type), &mut cx)?;
+      let numeric = arg.data_type.is_numeric() && param.data_type.is_numeric();
+      if !numeric && !arg.data_type.is_assignable_to(&param.data_type) {
+        return Err(error(
+          format!(
+            "mismatched types: `{}` is `{}` but the argument is `{}`",
+            param.name, param.data_type, arg.data_type
+          ),
+          arg.span,
+        ));
+      }
+      checked.push(arg);
+    }
+    Ok(Call {
+      index,
+      args: checked,
+      pair_str,
+    })
+  }
+
+  /// evaluate the arguments into the parameters slots and render the macro
+  /// body, a missing value is handled the same way as in an expression
+  /// placeholder, and the parameter has no value.
+  pub(crate) fn render_call(
+    &self,
+    call: &Call<'s>,
+    bindings: &HashMap<&str, &str>,
+    options: &RenderOptions,
+    locals: &mut Locals,
+    output: &mut String,
+  ) -> SigmaResult<'s, ()> {
+    let definition = &self.macros[call.index];
+    let mut values = Vec::with_capacity(call.args.len());
+    for (arg, param) in call.args.iter().zip(&definition.params) {
+      let value = match self.eval(arg, bindings, options, locals) {
+        Ok(value) => value.to_string(),
+        Err(Halt::Error(e)) => return Err(e),
+        Err(Halt::Empty) => {
+          values.push(None);
+          continue;
+        },
+        Err(Halt::Unbinded(index)) => {
+          let var = &self.vars[index];
+          match options.unbinded {
+            _ if var.nullable => {},
+            Unbinded::Error => {
+              return Err(unbinded_error(var, bindings.keys()))
+            },
+            Unbinded::Empty => {},
+            Unbinded::Verbatim => {
+              output.push_str(call.pair_str);
+              return Ok(());
+            },
+          }
+          values.push(None);
+          continue;
+        },
+      };
+      let invalid = || {
+        error(
+          format!("`{}` is not a valid `{}`", preview(&value), param.data_type),
+          arg.span,
+        )
+      };
+      if !self.is_valid(&param.data_type, &value) {
+        return Err(invalid());
+      }
+      values.push(Some(
+        Value::parse(&param.data_type, &value).ok_or_else(invalid)?,
+      ));
+    }
+    for (param, value) in definition.params.iter().zip(values) {
+      locals[param.slot] = value;
+    }
+    for node in &definition.body {
+      self.render_node(node, bindings, options, locals, output)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::Sigma;
+  use std::collections::HashMap;
+
+  fn render(input: &str, bindings: &[(&str, &str)]) -> String {
+    let sigma = Sigma::new(input).parse().unwrap();
+    let bindings: HashMap<_, _> = bindings.iter().cloned().collect();
+    sigma.render(&bindings).unwrap()
+  }
+
+  fn parse_error(input: &str) -> String {
+    Sigma::new(input).parse().unwrap_err().to_string()
+  }
+
+  #[test]
+  fn calls() {
+    let input = "{{# macro button(label, url) }}\
+                 <a href=\"{{ url }}\">{{ label | UPPERCASE }}</a>\
+                 {{/ macro }}\
+                 {{> button(\"Save\", save_url) }} \
+                 {{> button(\"Back\", \"/\") }}";
+    let bindings = [("save_url", "/save")];
+    assert_eq!(
+      render(input, &bindings),
+      "<a href=\"/save\">SAVE</a> <a href=\"/\">BACK</a>"
+    );
+    let sigma = Sigma::new(input).parse().unwrap();
+    let names: Vec<_> = sigma.variables().iter().map(|v| v.name).collect();
+    assert_eq!(names, vec!["save_url"]);
+    // called before the definition, with typed parameters and template
+    // variables in the body.
+    let input = "{{> price(qty * 2) }}\
+                 {{# macro price(n: u32) }}{{ n * unit }} {{ currency }}\
+                 {{# let n = 0 }}{{/ macro }}";
+    let bindings = [("qty", "2"), ("unit", "3"), ("currency", "EUR")];
+    assert_eq!(render(input, &bindings), "12 EUR");
+    let input = "{{# macro a(x) }}<{{ x }}>{{/ macro }}\
+                 {{# macro b(y) }}{{> a(y ~ y) }}{{/ macro }}{{> b(\"z\") }}";
+    assert_eq!(render(input, &[]), "<zz>");
+  }
+
+  #[test]
+  fn parse_errors() {
+    let button = "{{# macro button(label, url) }}{{/ macro }}";
+    let err = parse_error(&format!("{}{{{{> button(\"Save\") }}}}", button));
+    assert!(err.contains("macro `button` takes 2 argument(s) but 1 were"));
+    let err = parse_error(&format!("{}{{{{> buton(\"a\", \"b\") }}}}", button));
+    assert!(err.contains("did you mean: `button`"));
+    let err = parse_error("{{# macro m(n: u8) }}{{/ macro }}{{> m(true) }}");
+    assert!(err.contains("`n` is `U8` but the argument is `Bool`"));
+    let err = parse_error("{{# macro m() }}{{> m() }}{{/ macro }}");
+    assert!(err.contains("only call the macros defined before it"));
+    let err = parse_error("{{# macro m() }}{{/ macro }}{{# macro m() }}");
+    assert!(err.contains("unclosed macro"));
+    let err = parse_error(&format!("{}{}", button, button));
+    assert!(err.contains("macro `button` is already defined"));
+    assert!(parse_error("{{# macro m(a, a) }}{{/ macro }}").contains("`a`"));
+    assert!(parse_error("text {{/ macro }}").contains("no macro to close"));
+  }
+
+  #[test]
+  fn missing_arguments() {
+    let input = "{{# macro hi(name) }}Hi {{ name }}!{{/ macro }}{{> hi(n) }}";
+    let sigma = Sigma::new(input).parse().unwrap();
+    let err = sigma.render(&HashMap::new()).unwrap_err().to_string();
+    assert!(err.contains("unbinded variable: `n`"));
+    let sigma = Sigma::new(input).ignore_unbinded().parse().unwrap();
+    assert_eq!(sigma.render(&HashMap::new()).unwrap(), "{{> hi(n) }}");
+    let input = "{{ n?: str }}{{# macro hi(name) }}Hi {{ name }}!{{/ macro }}\
+                 {{> hi(n) }}";
+    assert_eq!(render(input, &[]), "Hi !");
+  }
+}
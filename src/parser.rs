@@ -12,12 +12,12 @@ pub(crate) struct SigmaParser;
 /// Thus in a list of possible values like ["foo", "bar"], the value "fop" will
 /// yield `Some("foo")`, whereas "blark" would yield `None`.
 /// see: https://github.com/clap-rs/clap/blob/master/src/suggestions.rs
-pub(crate) fn did_you_mean<'a, T: ?Sized, I>(
+pub(crate) fn did_you_mean<'a, T, I>(
   v: &str,
   possible_values: I,
 ) -> Option<&'a str>
 where
-  T: AsRef<str> + 'a,
+  T: AsRef<str> + ?Sized + 'a,
   I: IntoIterator<Item = &'a T>,
 {
   let mut candidate: Option<(f64, &str)> = None;
@@ -35,6 +35,26 @@ where
   }
 }
 
+/// the content of a quoted `string` without the quotes, and with the escaped
+/// chars replaced.
+pub(crate) fn unescape(quoted: &str) -> String {
+  let mut output = String::new();
+  let mut chars = quoted[1..quoted.len() - 1].chars();
+  while let Some(c) = chars.next() {
+    if c == '\\' {
+      match chars.next() {
+        Some('n') => output.push('\n'),
+        Some('t') => output.push('\t'),
+        Some(c) => output.push(c),
+        None => {},
+      }
+    } else {
+      output.push(c);
+    }
+  }
+  output
+}
+
 #[cfg(test)]
 mod test_did_you_mean {
   use super::*;
@@ -44,4 +64,9 @@ mod test_did_you_mean {
     let p_vals = ["test", "possible", "values"];
     assert_eq!(did_you_mean("tst", p_vals.iter()), Some("test"));
   }
+
+  #[test]
+  fn unescape_string() {
+    assert_eq!(unescape(r#""a \"b\" \\ \n""#), "a \"b\" \\ \n");
+  }
 }
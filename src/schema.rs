@@ -0,0 +1,183 @@
+use crate::{Constraint, DataType, Number, Sigma};
+use serde_json::{json, Map, Value};
+use std::convert::TryFrom;
+
+impl<'s> Sigma<'s> {
+  /// Describe the template input as a [JSON Schema](https://json-schema.org/)
+  /// document.
+  ///
+  /// every variable is a property of the root object, typed from its
+  /// declared data type (untyped variables are strings), and non-nullable
+  /// variables are `required`.
+  ///
+  /// Example:
+  /// ```
+  /// # use sigma::Sigma;
+  /// let sigma = Sigma::new("{{ name: str }} is {{ age?: u8 }}")
+  ///   .bind("name", "someone")
+  ///   .parse()
+  ///   .unwrap();
+  /// let schema = sigma.json_schema();
+  /// assert_eq!(schema["properties"]["age"]["maximum"], 255);
+  /// assert_eq!(schema["required"][0], "name");
+  /// ```
+  pub fn json_schema(&self) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for var in self.variables() {
+      let mut schema = match &var.data_type {
+        Some((data_type, _)) => data_type.json_schema(),
+        None => json!({ "type": "string" }),
+      };
+      if let Some((constraint, _)) = &var.constraint {
+        constraint.extend_json_schema(&mut schema);
+      }
+      properties.insert(var.name.to_owned(), schema);
+      if !var.nullable {
+        required.push(Value::from(var.name));
+      }
+    }
+    json!({
+      "$schema": "http://json-schema.org/draft-07/schema#",
+      "type": "object",
+      "properties": properties,
+      "required": required,
+    })
+  }
+}
+
+impl DataType {
+  fn json_schema(&self) -> Value {
+    use self::DataType::*;
+    match self {
+      U8 => integer(u8::MIN, u8::MAX),
+      I8 => integer(i8::MIN, i8::MAX),
+      U16 => integer(u16::MIN, u16::MAX),
+      I16 => integer(i16::MIN, i16::MAX),
+      U32 => integer(u32::MIN, u32::MAX),
+      I32 => integer(i32::MIN, i32::MAX),
+      U64 => integer(u64::MIN, u64::MAX),
+      I64 => integer(i64::MIN, i64::MAX),
+      Usize => integer(usize::MIN as u64, usize::MAX as u64),
+      Isize => integer(isize::MIN as i64, isize::MAX as i64),
+      // 128-bit bounds are out of the JSON number range.
+      U128 => json!({ "type": "integer", "minimum": 0 }),
+      I128 => json!({ "type": "integer" }),
+      F32 | F64 => json!({ "type": "number" }),
+      Bool => json!({ "type": "boolean" }),
+      Char => json!({ "type": "string", "minLength": 1, "maxLength": 1 }),
+      Str | Enum | Custom(_) => json!({ "type": "string" }),
+      NonEmptyStr => json!({ "type": "string", "minLength": 1 }),
+      Email => string_format("email"),
+      Url => string_format("uri"),
+      Uuid => string_format("uuid"),
+      Ipv4 => string_format("ipv4"),
+      Ipv6 => string_format("ipv6"),
+      Date => string_format("date"),
+      DateTime => string_format("date-time"),
+    }
+  }
+}
+
+impl Constraint {
+  fn extend_json_schema(&self, schema: &mut Value) {
+    match self {
+      Constraint::Range(bounds) => {
+        if let Some(start) = bounds.start {
+          schema["minimum"] = number(start);
+        }
+        if let Some(end) = bounds.end {
+          if bounds.inclusive {
+            schema["maximum"] = number(end);
+          } else {
+            schema.as_object_mut().unwrap().remove("maximum");
+            schema["exclusiveMaximum"] = number(end);
+          }
+        }
+      },
+      Constraint::Length(bounds) => {
+        if let Some(start) = bounds.start {
+          schema["minLength"] = number(start);
+        }
+        // a length range is never empty, so `..end` has `end > 0`.
+        if let Some(Number::Uint(end)) = bounds.end {
+          let end = if bounds.inclusive { end } else { end - 1 };
+          schema["maxLength"] = number(Number::Uint(end));
+        }
+      },
+      Constraint::Pattern(pattern) => {
+        schema["pattern"] = pattern.as_str().into();
+      },
+      Constraint::OneOf(variants) => {
+        schema["enum"] = variants.clone().into();
+      },
+    }
+  }
+}
+
+/// a bound as a JSON number, the 128-bit ones out of the 64-bit range are
+/// approximated as floats.
+fn number(number: Number) -> Value {
+  match number {
+    Number::Int(n) => i64::try_from(n).map_or((n as f64).into(), Value::from),
+    Number::Uint(n) => u64::try_from(n).map_or((n as f64).into(), Value::from),
+    Number::Float(n) => n.into(),
+  }
+}
+
+fn integer<T: Into<Value>>(min: T, max: T) -> Value {
+  json!({ "type": "integer", "minimum": min.into(), "maximum": max.into() })
+}
+
+fn string_format(format: &str) -> Value {
+  json!({ "type": "string", "format": format })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn schema_from_variables() {
+    let sigma = Sigma::new("{{ a: i16 }} {{ b?: bool }} {{ c }}")
+      .bind("a", "1")
+      .bind("c", "x")
+      .parse()
+      .unwrap();
+    let schema = sigma.json_schema();
+    assert_eq!(
+      schema["properties"],
+      json!({
+        "a": { "type": "integer", "minimum": -32768, "maximum": 32767 },
+        "b": { "type": "boolean" },
+        "c": { "type": "string" },
+      })
+    );
+    assert_eq!(schema["required"], json!(["a", "c"]));
+  }
+
+  #[test]
+  fn schema_from_constraints() {
+    let sigma = Sigma::new(
+      "{{ a: i16(-5..10) }} {{ b: str(len ..=3) }} {{ c: enum(x, y) }}",
+    )
+    .parse()
+    .unwrap();
+    let schema = sigma.json_schema();
+    assert_eq!(
+      schema["properties"],
+      json!({
+        "a": { "type": "integer", "minimum": -5, "exclusiveMaximum": 10 },
+        "b": { "type": "string", "maxLength": 3 },
+        "c": { "type": "string", "enum": ["x", "y"] },
+      })
+    );
+    // the bounds could have leading zeros, unlike the JSON numbers.
+    let sigma = Sigma::new("{{ a: u8(007..=10) }} {{ b: str(len 00..=3) }}")
+      .parse()
+      .unwrap();
+    let schema = sigma.json_schema();
+    assert_eq!(schema["properties"]["a"]["minimum"], 7);
+    assert_eq!(schema["properties"]["b"]["minLength"], 0);
+  }
+}